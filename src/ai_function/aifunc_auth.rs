@@ -0,0 +1,34 @@
+use ai_functions::function_to_string;
+
+#[function_to_string]
+pub fn print_auth_webserver_code(_backend_code_and_database_code: &str) {
+    /// INPUT: Takes in the working actix-web BACKEND_CODE of a webserver and its DATABASE_MODULE (src/db.rs) if there is one
+    /// FUNCTION: Adds user authentication to the BACKEND_CODE without breaking any of the existing routes
+    /// IMPORTANT: Adds exactly these routes, all bodies and responses are JSON:
+    ///   POST /auth/signup  body {"username": string, "password": string} -> 200 {"id": number, "username": string}, 409 if the username is taken
+    ///   POST /auth/login   body {"username": string, "password": string} -> 200 {"token": string}, 401 on wrong credentials
+    ///   GET  /auth/me      header "Authorization: Bearer <token>" -> 200 {"id": number, "username": string}, 401 without a valid token
+    ///   POST /auth/logout  header "Authorization: Bearer <token>" -> 200, the token is invalid afterwards
+    /// IMPORTANT: Passwords are hashed with argon2 (argon2::Argon2 and password_hash::SaltString), never stored or logged in plain text
+    ///   Sessions are random uuid v4 tokens kept in a Mutex<HashMap<String, u64>> in the app data, removed on logout
+    ///   Users are stored with the DATABASE_MODULE functions if one is provided, otherwise in the app data
+    /// IMPORTANT: The following libraries are already installed
+    ///   reqwest, serde, serde_json, tokio, actix-web, async-trait, actix_cors, argon2, uuid, rusqlite
+    /// IMPORTANT: Keep reading the port from the PORT environment variable like the BACKEND_CODE does
+    /// OUTPUT: Print ONLY the full code of main.rs, nothing else. This function ONLY prints code.
+    println!(OUTPUT)
+}
+
+#[function_to_string]
+pub fn print_fixed_auth_code(_broken_code_with_bugs: &str) {
+    /// INPUT: Takes in the actix-web BROKEN_CODE of a webserver with authentication, the API_ENDPOINT_SCHEMA of the routes it had before and the ERROR_BUGS found
+    /// FUNCTION: Removes the bugs while keeping every route of the API_ENDPOINT_SCHEMA working with a valid bearer token
+    /// IMPORTANT: The authentication routes must keep exactly this contract, all bodies and responses are JSON:
+    ///   POST /auth/signup  body {"username": string, "password": string} -> 200 {"id": number, "username": string}, 409 if the username is taken
+    ///   POST /auth/login   body {"username": string, "password": string} -> 200 {"token": string}, 401 on wrong credentials
+    ///   GET  /auth/me      header "Authorization: Bearer <token>" -> 200 {"id": number, "username": string}, 401 without a valid token
+    ///   POST /auth/logout  header "Authorization: Bearer <token>" -> 200, the token is invalid afterwards
+    /// IMPORTANT: Passwords stay hashed with argon2, never stored or logged in plain text
+    /// OUTPUT: Print ONLY the full code of main.rs, nothing else. This function ONLY prints code.
+    println!(OUTPUT)
+}
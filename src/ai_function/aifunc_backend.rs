@@ -9,6 +9,11 @@ pub fn print_backend_webserver_code(_project_description_and_template: &str) {
     /// IMPORTANT: The following libraries are already installed
     ///   reqwest, serde, serde_json, tokio, actix-web, async-trait, actix_cors
     /// No other external libraries should be used. Write functions that fit with the description from the PROJECT_DESCRIPTION
+    /// IMPORTANT: If a DATABASE_MODULE is provided, it is already saved as src/db.rs and rusqlite is installed.
+    ///   Declare `mod db;` and store all data through its functions instead of a json file or in memory state
+    /// IMPORTANT: Files may only be read or written under the data/ directory, eg. "./data/database.json".
+    ///   No unsafe code and no std::process
+    /// IMPORTANT: The server binds 127.0.0.1 on the port read from the PORT environment variable, falling back to 8080
     /// OUTPUT: Print ONLY the code, nothing else. This function ONLY prints code.
     println!(OUTPUT)
 }
@@ -22,6 +27,9 @@ pub fn print_improved_webserver_code(_project_description_and_template: &str) {
     ///   3. ONLY writes the code. No commentary.
     /// IMPORTANT: The following libraries are already installed. Does not use ANY libraries other than what was provided in the template
     ///   reqwest, serde, serde_json, tokio, actix-web, async-trait
+    /// IMPORTANT: If the PROJECT_DESCRIPTION has database_code, keep `mod db;` and the calls into it. rusqlite is installed for it.
+    /// IMPORTANT: Files may only be read or written under the data/ directory. No unsafe code and no std::process
+    /// IMPORTANT: The server binds 127.0.0.1 on the port read from the PORT environment variable, falling back to 8080
     println!(OUTPUT)
 }
 
@@ -0,0 +1,34 @@
+use ai_functions::function_to_string;
+
+#[function_to_string]
+pub fn print_database_migrations(_project_description_and_scope: &str) {
+    /// INPUT: Takes in a PROJECT_DESCRIPTION and PROJECT_SCOPE for a website backend build
+    /// FUNCTION: Designs the SQLite schema the backend needs to store its data and prints it as a migration
+    /// IMPORTANT: Only uses SQLite syntax. Every statement ends with a semicolon.
+    ///   Uses CREATE TABLE IF NOT EXISTS, INTEGER PRIMARY KEY AUTOINCREMENT ids, NOT NULL where it makes sense and FOREIGN KEY constraints between tables
+    /// IMPORTANT: If the PROJECT_SCOPE requires user login and logout, a users table with a unique username and a password_hash column is included
+    /// OUTPUT: Print ONLY the SQL, nothing else. No commentary, no markdown.
+    println!(OUTPUT)
+}
+
+#[function_to_string]
+pub fn print_database_access_code(_database_migrations: &str) {
+    /// INPUT: Takes in the SQLite DATABASE_MIGRATIONS of a website backend
+    /// FUNCTION: Writes a Rust module (saved as src/db.rs) with the data access code for every table using rusqlite
+    /// IMPORTANT: The module exposes:
+    ///   pub fn open_database(path: &str) -> rusqlite::Result<rusqlite::Connection> which opens the file and runs the migrations (embedded with include_str!("../migrations/0001_init.sql"))
+    ///   one serde (Serialize, Deserialize, Clone) struct per table
+    ///   create, read by id, read all, update and delete functions per table taking &rusqlite::Connection
+    /// IMPORTANT: The following libraries are already installed
+    ///   rusqlite, serde, serde_json
+    /// OUTPUT: Print ONLY the code, nothing else. This function ONLY prints code.
+    println!(OUTPUT)
+}
+
+#[function_to_string]
+pub fn print_fixed_database_migrations(_broken_migrations_with_bugs: &str) {
+    /// INPUT: Takes in SQLite BROKEN_MIGRATIONS and the ERROR_BUGS SQLite reported when running them
+    /// FUNCTION: Removes the bugs from the migrations
+    /// IMPORTANT: Only prints out the fixed SQL. No commentary or anything else
+    println!(OUTPUT)
+}
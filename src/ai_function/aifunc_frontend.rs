@@ -0,0 +1,23 @@
+use ai_functions::function_to_string;
+
+#[function_to_string]
+pub fn print_frontend_client_code(_project_description_and_api_schema: &str) {
+    /// INPUT: Takes in a PROJECT_DESCRIPTION, the API_BASE_URL of a running backend and its API_ENDPOINT_SCHEMA
+    /// FUNCTION: Writes a single static HTML page with inline CSS and vanilla JavaScript that works as the client of the backend
+    /// IMPORTANT: Every route in the API_ENDPOINT_SCHEMA gets a section in the page that calls it with fetch()
+    ///   Routes are used exactly as written in the schema, e.g. `${API_BASE_URL}/item/${id}` for "/item/{id}"
+    ///   Request bodies are sent as JSON built from form inputs matching the "request_body" keys
+    ///   Responses are rendered using the "response" keys
+    /// IMPORTANT: Declare `const API_BASE_URL = "<API_BASE_URL>";` once at the top of the script and use it for every call
+    /// IMPORTANT: No external libraries, CDNs, fonts or build steps. Everything lives in the one HTML document.
+    /// OUTPUT: Print ONLY the HTML document starting with <!DOCTYPE html>, nothing else. This function ONLY prints code.
+    println!(OUTPUT)
+}
+
+#[function_to_string]
+pub fn print_fixed_frontend_code(_broken_frontend_code_with_bugs: &str) {
+    /// INPUT: Takes in a static HTML/JavaScript BROKEN_CODE, the API_ENDPOINT_SCHEMA it should call and the ERROR_BUGS found
+    /// FUNCTION: Removes the bugs and makes sure every route in the API_ENDPOINT_SCHEMA is called by the page
+    /// IMPORTANT: Only prints out the new and improved HTML document. No commentary or anything else
+    println!(OUTPUT)
+}
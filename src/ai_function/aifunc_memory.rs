@@ -0,0 +1,11 @@
+use ai_functions::function_to_string;
+
+#[function_to_string]
+pub fn print_memory_summary(_conversation_history: &str) {
+    /// INPUT: Takes in the CONVERSATION_HISTORY of an agent building a Rust webserver, oldest message first
+    /// FUNCTION: Compresses the history into a short summary that the agent can continue working from
+    /// IMPORTANT: Keep every decision that was made, every approach that already failed and why it failed
+    /// IMPORTANT: Do not repeat code. Mention function and struct names instead.
+    /// OUTPUT: Prints ONLY the summary as plain text, nothing else.
+    println!(OUTPUT)
+}
@@ -0,0 +1,23 @@
+use ai_functions::function_to_string;
+
+#[function_to_string]
+pub fn print_security_review(_code_input: &str) {
+    /// INPUT: Takes in AI generated Rust webserver CODE_INPUT that is about to be built and executed on the user's machine
+    /// FUNCTION: Reviews the code like a security auditor and lists everything that could harm the machine or leak data
+    /// LOGIC: Looks for, among others:
+    ///   process spawning (std::process::Command, tokio::process), filesystem writes or deletes outside the project directory,
+    ///   network listeners on 0.0.0.0, unsafe blocks, hard-coded secrets (api keys, passwords, tokens),
+    ///   calls to urls that have nothing to do with the project, disabled TLS verification, unbounded loops or allocations
+    /// IMPORTANT: Only prints out a JSON list. No commentary or anything else. Prints [] if nothing was found.
+    /// IMPORTANT: "severity" is one of "low", "medium", "high". "line" is the 1-based line number or null.
+    /// EXAMPLE OUTPUT:
+    /// [
+    ///   {
+    ///     "severity": "high",
+    ///     "category": "process_spawn",
+    ///     "line": 42,
+    ///     "description": "Runs `rm -rf` through std::process::Command with user input"
+    ///   }
+    /// ]
+    println!(OUTPUT)
+}
@@ -0,0 +1,16 @@
+use ai_functions::function_to_string;
+
+#[function_to_string]
+pub fn print_integration_tests(_backend_code_and_api_schema: &str) {
+    /// INPUT: Takes in the actix-web BACKEND_CODE of a webserver (src/main.rs) and its API_ENDPOINT_SCHEMA
+    /// FUNCTION: Writes a Rust test module saved as src/generated_tests.rs and declared in main.rs as `#[cfg(test)] mod generated_tests;`
+    /// IMPORTANT: The module starts with `use super::*;` and `use actix_web::{test, web, App};`
+    ///   Every route in the API_ENDPOINT_SCHEMA gets at least one #[actix_web::test] async test
+    ///   Each test builds the app with test::init_service(App::new()...) registering the same app_data and routes as main.rs does
+    ///   Requests are built with test::TestRequest using the route's method, a JSON body from "request_body" and concrete values for {path} parameters
+    ///   Tests assert the status code and deserialize the body into serde_json::Value to check the "response" keys
+    ///   Resources are created first (e.g. POST) before they are read, updated or deleted in the same test
+    /// IMPORTANT: Tests never call external urls and never depend on each other
+    /// OUTPUT: Print ONLY the code of the test module, nothing else. This function ONLY prints code.
+    println!(OUTPUT)
+}
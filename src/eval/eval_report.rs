@@ -0,0 +1,129 @@
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+
+use crate::util::run_metrics::RunMetrics;
+
+// outcome of one benchmark request
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct EvalResult {
+    pub id: String,
+    pub is_completed: bool,
+    pub metrics: RunMetrics,
+}
+
+pub fn load_eval_results(path: &str) -> Option<Vec<EvalResult>> {
+    let results_str = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&results_str).ok()
+}
+
+pub fn save_eval_results(path: &str, results: &Vec<EvalResult>) {
+    let results_str =
+        serde_json::to_string_pretty(results).expect("Failed to serialize evaluation results");
+
+    fs::write(path, results_str).expect("Failed to save evaluation results");
+}
+
+fn format_bool(value: Option<bool>) -> &'static str {
+    match value {
+        Some(true) => "yes",
+        Some(false) => "no",
+        None => "-",
+    }
+}
+
+fn format_delta(current: i64, baseline: Option<i64>) -> String {
+    match baseline {
+        Some(baseline) if current > baseline => format!("+{}", current - baseline),
+        Some(baseline) => format!("{}", current - baseline),
+        None => "-".to_string(),
+    }
+}
+
+// render the results as a table, with deltas against the baseline run if one is provided
+pub fn build_comparison_table(results: &[EvalResult], baseline: Option<&[EvalResult]>) -> String {
+    let mut table = format!(
+        "{:<22} {:>5} {:>6} {:>10} {:>6} {:>10} {:>8} | {:>7} {:>7} {:>8}\n",
+        "REQUEST",
+        "DONE",
+        "SCOPE",
+        "1ST BUILD",
+        "FIXES",
+        "ENDPOINTS",
+        "TOKENS",
+        "Δ FIXES",
+        "Δ 200s",
+        "Δ TOKENS"
+    );
+
+    for result in results {
+        let metrics = &result.metrics;
+        let baseline_metrics = baseline
+            .and_then(|baseline| baseline.iter().find(|entry| entry.id == result.id))
+            .map(|entry| &entry.metrics);
+
+        table.push_str(&format!(
+            "{:<22} {:>5} {:>6} {:>10} {:>6} {:>10} {:>8} | {:>7} {:>7} {:>8}\n",
+            result.id,
+            format_bool(Some(result.is_completed)),
+            format_bool(Some(metrics.is_scope_decoded)),
+            format_bool(metrics.is_compiled_first_try),
+            metrics.fix_iterations,
+            format!("{}/{}", metrics.endpoints_passed, metrics.endpoints_tested),
+            metrics.total_tokens,
+            format_delta(
+                metrics.fix_iterations as i64,
+                baseline_metrics.map(|m| m.fix_iterations as i64)
+            ),
+            format_delta(
+                metrics.endpoints_passed as i64,
+                baseline_metrics.map(|m| m.endpoints_passed as i64)
+            ),
+            format_delta(
+                metrics.total_tokens as i64,
+                baseline_metrics.map(|m| m.total_tokens as i64)
+            ),
+        ));
+    }
+
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval_result(id: &str, fix_iterations: u32, total_tokens: u64) -> EvalResult {
+        EvalResult {
+            id: id.to_string(),
+            is_completed: true,
+            metrics: RunMetrics {
+                is_scope_decoded: true,
+                is_compiled_first_try: Some(fix_iterations == 0),
+                fix_iterations,
+                endpoints_tested: 3,
+                endpoints_passed: 2,
+                total_tokens,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_comparison_table_with_baseline() {
+        let results = vec![
+            eval_result("todo-app", 1, 1200),
+            eval_result("new-one", 0, 500),
+        ];
+        let baseline = vec![eval_result("todo-app", 2, 1000)];
+
+        let table = build_comparison_table(&results, Some(&baseline));
+        let lines: Vec<&str> = table.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].starts_with("todo-app"));
+        assert!(lines[1].contains("2/3"));
+        assert!(lines[1].ends_with("-1       0     +200"));
+        assert!(lines[2].ends_with("-       -        -"));
+    }
+}
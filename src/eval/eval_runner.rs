@@ -0,0 +1,164 @@
+use std::fs;
+use std::sync::Arc;
+
+use serde::Deserialize;
+
+use super::eval_report::{
+    build_comparison_table, load_eval_results, save_eval_results, EvalResult,
+};
+use crate::model::agent_manager::agent_manager::AgentManager;
+use crate::model::agent_manager::agent_registry::AgentRegistry;
+use crate::model::common::large_language_model::LlmProvider;
+use crate::service::call_open_api::set_llm_provider;
+use crate::util::command_line::{set_auto_approve, PrintCommand};
+use crate::util::run_metrics::{get_run_metrics, reset_run_metrics};
+
+const DEFAULT_CORPUS_PATH: &str = "benchmarks/eval_corpus.json";
+const DEFAULT_BASELINE_PATH: &str = "benchmarks/eval_baseline.json";
+const DEFAULT_OUTPUT_PATH: &str = "benchmarks/eval_results.json";
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct BenchmarkRequest {
+    pub id: String,
+    pub request: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvalConfig {
+    pub corpus_path: String,
+    pub baseline_path: String,
+    pub output_path: String,
+    pub provider: LlmProvider,
+}
+
+impl EvalConfig {
+    // parse `hoppity eval [--corpus PATH] [--baseline PATH] [--output PATH] [--provider NAME] [--url URL] [--model NAME]`
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut config = Self {
+            corpus_path: DEFAULT_CORPUS_PATH.to_string(),
+            baseline_path: DEFAULT_BASELINE_PATH.to_string(),
+            output_path: DEFAULT_OUTPUT_PATH.to_string(),
+            provider: LlmProvider::from_env(),
+        };
+
+        let mut args_iter = args.iter();
+
+        while let Some(flag) = args_iter.next() {
+            let value = args_iter
+                .next()
+                .ok_or(format!("Missing value for {}", flag))?
+                .clone();
+
+            match flag.as_str() {
+                "--corpus" => config.corpus_path = value,
+                "--baseline" => config.baseline_path = value,
+                "--output" => config.output_path = value,
+                "--provider" => config.provider.name = value,
+                "--url" => config.provider.url = value,
+                "--model" => config.provider.model = value,
+                _ => return Err(format!("Unknown eval option: {}", flag)),
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+pub fn load_corpus(path: &str) -> Vec<BenchmarkRequest> {
+    let corpus_str = fs::read_to_string(path).expect("Failed to read evaluation corpus");
+    serde_json::from_str(&corpus_str).expect("Failed to decode evaluation corpus")
+}
+
+// run a single request through the whole pipeline
+// a panic outside the agents only takes down its own blocking task and runtime, the eval worker keeps going
+async fn run_benchmark_request(
+    request: &BenchmarkRequest,
+    agent_registry: Arc<AgentRegistry>,
+) -> EvalResult {
+    reset_run_metrics();
+
+    let user_request = request.request.clone();
+
+    let run_result = tokio::task::spawn_blocking(move || {
+        let runtime = tokio::runtime::Runtime::new().expect("Failed to create evaluation runtime");
+
+        runtime.block_on(async {
+            let mut agent_manager = AgentManager::new(user_request.as_str(), &agent_registry)
+                .await
+                .expect("Failed to create Agent Manager");
+
+            agent_manager.execute_manager().await
+        })
+    })
+    .await;
+
+    EvalResult {
+        id: request.id.clone(),
+        is_completed: matches!(run_result, Ok(Ok(()))),
+        metrics: get_run_metrics(),
+    }
+}
+
+pub async fn run_eval(config: EvalConfig, agent_registry: Arc<AgentRegistry>) {
+    set_llm_provider(config.provider.clone()).expect("LLM provider was already initialized");
+    set_auto_approve(true);
+
+    let corpus = load_corpus(&config.corpus_path);
+    let mut results: Vec<EvalResult> = Vec::new();
+
+    for request in &corpus {
+        PrintCommand::UnitTest.print_agent_action(
+            "Evaluator",
+            format!(
+                "Running benchmark request {} with {} ({})",
+                request.id, config.provider.model, config.provider.name
+            )
+            .as_str(),
+        );
+
+        results.push(run_benchmark_request(request, agent_registry.clone()).await);
+    }
+
+    let baseline = load_eval_results(&config.baseline_path);
+
+    if baseline.is_none() {
+        PrintCommand::Issue.print_agent_action(
+            "Evaluator",
+            format!("No baseline found at {}", config.baseline_path).as_str(),
+        );
+    }
+
+    println!("{}", build_comparison_table(&results, baseline.as_deref()));
+
+    save_eval_results(&config.output_path, &results);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eval_config_from_args() {
+        let args: Vec<String> = ["--model", "gpt-3.5-turbo", "--baseline", "old.json"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+
+        let config = EvalConfig::from_args(&args).unwrap();
+
+        assert_eq!(config.provider.model, "gpt-3.5-turbo");
+        assert_eq!(config.baseline_path, "old.json");
+        assert_eq!(config.corpus_path, DEFAULT_CORPUS_PATH);
+
+        assert!(EvalConfig::from_args(&["--model".to_string()]).is_err());
+        assert!(EvalConfig::from_args(&["--color".to_string(), "red".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_load_corpus() {
+        let corpus = load_corpus(DEFAULT_CORPUS_PATH);
+
+        assert!(!corpus.is_empty());
+        assert!(corpus.iter().all(|request| !request.request.is_empty()));
+    }
+}
@@ -0,0 +1,2 @@
+pub mod eval_report;
+pub mod eval_runner;
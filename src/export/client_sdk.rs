@@ -0,0 +1,340 @@
+use crate::export::openapi::operation_id;
+use crate::model::common::api_schema::{FormattedRouteObject, SchemaType};
+use crate::util::common::dependency_version;
+
+pub const CLIENT_CRATE_NAME: &str = "hoppity_client";
+
+// used when the generated project does not depend on the crate itself
+const DEFAULT_REQWEST_VERSION: &str = "0.11";
+const DEFAULT_SERDE_VERSION: &str = "1.0";
+const DEFAULT_SERDE_JSON_VERSION: &str = "1.0";
+
+// keywords that can not be written as raw identifiers either
+const NON_RAW_KEYWORDS: [&str; 5] = ["self", "Self", "super", "crate", "_"];
+const KEYWORDS: [&str; 38] = [
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+    "return", "static", "struct", "trait", "true", "type", "unsafe", "use", "where", "while",
+    "abstract", "box", "final", "try",
+];
+
+// "isDone", "is-done" -> "is_done"
+fn snake_case(name: &str) -> String {
+    let mut snake_name = String::new();
+
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            if !snake_name.is_empty() && !snake_name.ends_with('_') {
+                snake_name.push('_');
+            }
+            snake_name.push(c.to_ascii_lowercase());
+        } else if c.is_ascii_alphanumeric() {
+            snake_name.push(c);
+        } else if !snake_name.is_empty() && !snake_name.ends_with('_') {
+            snake_name.push('_');
+        }
+    }
+
+    let snake_name = snake_name.trim_end_matches('_').to_string();
+    if snake_name.is_empty() || snake_name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("field_{}", snake_name)
+    } else {
+        snake_name
+    }
+}
+
+// "post_item_id" -> "PostItemId"
+fn pascal_case(name: &str) -> String {
+    snake_case(name)
+        .split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+fn rust_ident(name: &str) -> String {
+    let ident = snake_case(name);
+
+    if NON_RAW_KEYWORDS.contains(&ident.as_str()) {
+        format!("{}_", ident)
+    } else if KEYWORDS.contains(&ident.as_str()) {
+        format!("r#{}", ident)
+    } else {
+        ident
+    }
+}
+
+// the structs of the client, one per object in a body
+#[derive(Debug, Default)]
+struct ClientTypes {
+    structs: Vec<(String, String)>,
+}
+
+impl ClientTypes {
+    // the rust type of a schema, objects become structs named after where they appear
+    fn rust_type(&mut self, schema: &SchemaType, type_name: &str) -> String {
+        match schema {
+            // the extractor writes "number" for integer fields, an actix handler rejects 1.0 for a u64
+            SchemaType::Number => "i64".to_string(),
+            SchemaType::Float => "f64".to_string(),
+            SchemaType::Bool => "bool".to_string(),
+            SchemaType::String => "String".to_string(),
+            SchemaType::Array(item_schema) => {
+                format!(
+                    "Vec<{}>",
+                    self.rust_type(item_schema, &format!("{}Item", type_name))
+                )
+            }
+            SchemaType::Optional(inner_schema) => {
+                format!("Option<{}>", self.rust_type(inner_schema, type_name))
+            }
+            SchemaType::Object(fields) if fields.is_empty() => {
+                "serde_json::Map<String, serde_json::Value>".to_string()
+            }
+            SchemaType::Object(fields) => {
+                let struct_fields: Vec<String> = fields
+                    .iter()
+                    .map(|(key, field_schema)| {
+                        let field_ident = rust_ident(key);
+                        let field_type = self
+                            .rust_type(field_schema, &format!("{}{}", type_name, pascal_case(key)));
+
+                        let rename = if field_ident.trim_start_matches("r#") != key {
+                            format!("    #[serde(rename = \"{}\")]\n", key.escape_default())
+                        } else {
+                            String::new()
+                        };
+                        format!("{}    pub {}: {},\n", rename, field_ident, field_type)
+                    })
+                    .collect();
+
+                self.add_struct(type_name, &struct_fields.concat())
+            }
+            SchemaType::None | SchemaType::NotProvided | SchemaType::Other(_) => {
+                "serde_json::Value".to_string()
+            }
+        }
+    }
+
+    // an identical struct is reused, a different one with the same name gets a number
+    fn add_struct(&mut self, type_name: &str, struct_body: &str) -> String {
+        let mut struct_name = type_name.to_string();
+        let mut suffix = 2;
+
+        loop {
+            match self.structs.iter().find(|(name, _)| *name == struct_name) {
+                Some((_, body)) if body == struct_body => return struct_name,
+                Some(_) => {
+                    struct_name = format!("{}{}", type_name, suffix);
+                    suffix += 1;
+                }
+                None => {
+                    self.structs
+                        .push((struct_name.clone(), struct_body.to_string()));
+                    return struct_name;
+                }
+            }
+        }
+    }
+
+    fn to_code(&self) -> String {
+        self.structs
+            .iter()
+            .map(|(name, body)| {
+                format!(
+                    "#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]\npub struct {} {{\n{}}}\n",
+                    name, body
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+fn client_method(
+    route_obj: &FormattedRouteObject,
+    method_name: &str,
+    types: &mut ClientTypes,
+) -> String {
+    let type_name = pascal_case(method_name);
+
+    let mut params = vec!["&self".to_string()];
+    params.extend(
+        route_obj
+            .path_params
+            .iter()
+            .map(|param| format!("{}: &str", rust_ident(param))),
+    );
+
+    // "/item/{id}" -> format!("{}/item/{}", self.base_url, id)
+    let url_template = route_obj
+        .route
+        .split('/')
+        .map(
+            |segment| match segment.starts_with('{') && segment.ends_with('}') {
+                true => "{}",
+                false => segment,
+            },
+        )
+        .collect::<Vec<&str>>()
+        .join("/");
+    let url_args: Vec<String> = std::iter::once("self.base_url".to_string())
+        .chain(route_obj.path_params.iter().map(|param| rust_ident(param)))
+        .collect();
+
+    let mut request = format!(
+        "        self.client\n            .request(reqwest::Method::{}, format!(\"{{}}{}\", {}))\n",
+        route_obj.method.as_str(),
+        url_template,
+        url_args.join(", ")
+    );
+
+    if !route_obj.request_body.is_empty() {
+        let body_type = types.rust_type(&route_obj.request_body, &format!("{}Request", type_name));
+        params.push(format!("body: &{}", body_type));
+        request.push_str("            .json(body)\n");
+    }
+    request.push_str("            .send()\n            .await?\n");
+
+    let (return_type, response_handling) = match &route_obj.response {
+        SchemaType::None => (
+            "()".to_string(),
+            "            .error_for_status()?;\n        Ok(())\n".to_string(),
+        ),
+        // nothing is known about the body, the caller reads it
+        SchemaType::NotProvided => (
+            "reqwest::Response".to_string(),
+            "            .error_for_status()\n".to_string(),
+        ),
+        response => (
+            types.rust_type(response, &format!("{}Response", type_name)),
+            "            .error_for_status()?\n            .json()\n            .await\n"
+                .to_string(),
+        ),
+    };
+
+    format!(
+        "    // {} {}\n    pub async fn {}({}) -> Result<{}, reqwest::Error> {{\n{}{}    }}\n",
+        route_obj.method,
+        route_obj.route,
+        method_name,
+        params.join(", "),
+        return_type,
+        request,
+        response_handling
+    )
+}
+
+// src/lib.rs of the client crate, one async method per route
+pub fn client_lib(api_schema: &[FormattedRouteObject]) -> String {
+    let mut types = ClientTypes::default();
+    let mut method_names: Vec<String> = Vec::new();
+    let mut methods: Vec<String> = Vec::new();
+
+    for route_obj in api_schema {
+        let base_name = snake_case(&operation_id(route_obj.method, &route_obj.route));
+        let mut method_name = base_name.clone();
+        let mut suffix = 2;
+        while method_names.contains(&method_name) {
+            method_name = format!("{}_{}", base_name, suffix);
+            suffix += 1;
+        }
+
+        methods.push(client_method(route_obj, &method_name, &mut types));
+        method_names.push(method_name);
+    }
+
+    format!(
+        "// generated by hoppity from the api schema of the generated server, changes are overwritten\n\n\
+         {}\n\
+         #[derive(Debug, Clone)]\n\
+         pub struct ApiClient {{\n    client: reqwest::Client,\n    base_url: String,\n}}\n\n\
+         impl ApiClient {{\n\
+         \x20   pub fn new(base_url: &str) -> Self {{\n        Self::with_client(reqwest::Client::new(), base_url)\n    }}\n\n\
+         \x20   pub fn with_client(client: reqwest::Client, base_url: &str) -> Self {{\n        Self {{\n            client,\n            base_url: base_url.trim_end_matches('/').to_string(),\n        }}\n    }}\n\n\
+         {}}}\n",
+        types.to_code(),
+        methods.join("\n")
+    )
+}
+
+// Cargo.toml of the client crate, on the versions the generated project already builds with
+pub fn client_cargo_toml(project_cargo_toml: &str) -> String {
+    let version = |crate_name: &str, default_version: &str| {
+        dependency_version(project_cargo_toml, crate_name).unwrap_or(default_version.to_string())
+    };
+
+    format!(
+        "[package]\nname = \"{}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n\
+         [dependencies]\n\
+         reqwest = {{ version = \"{}\", features = [\"json\"] }}\n\
+         serde = {{ version = \"{}\", features = [\"derive\"] }}\n\
+         serde_json = \"{}\"\n",
+        CLIENT_CRATE_NAME,
+        version("reqwest", DEFAULT_REQWEST_VERSION),
+        version("serde", DEFAULT_SERDE_VERSION),
+        version("serde_json", DEFAULT_SERDE_JSON_VERSION)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::common::api_schema::HttpMethod;
+    use serde_json::json;
+
+    #[test]
+    fn test_client_lib() {
+        let item = json!({ "id": "number", "isDone": "bool", "type": "string", "price": "float" });
+        let api_schema = vec![
+            FormattedRouteObject::new(
+                "/item",
+                HttpMethod::Post,
+                SchemaType::from(item.clone()),
+                SchemaType::from(item),
+            ),
+            FormattedRouteObject::new(
+                "/item/{id}",
+                HttpMethod::Delete,
+                SchemaType::None,
+                SchemaType::None,
+            ),
+        ];
+
+        let client_lib = client_lib(&api_schema);
+
+        assert!(client_lib.contains(
+            "pub struct PostItemRequest {\n    pub id: i64,\n    #[serde(rename = \"isDone\")]\n    pub is_done: bool,\n    pub price: f64,\n    pub r#type: String,\n}"
+        ));
+        assert!(client_lib.contains(
+            "pub async fn post_item(&self, body: &PostItemRequest) -> Result<PostItemResponse, reqwest::Error> {"
+        ));
+        assert!(client_lib.contains(
+            "pub async fn delete_item_id(&self, id: &str) -> Result<(), reqwest::Error> {"
+        ));
+        assert!(client_lib.contains(
+            ".request(reqwest::Method::DELETE, format!(\"{}/item/{}\", self.base_url, id))"
+        ));
+        assert!(syn::parse_file(&client_lib).is_ok());
+    }
+
+    #[test]
+    fn test_client_cargo_toml() {
+        let project_cargo_toml = "[dependencies]\nserde = { version = \"1.0.188\", features = [\"derive\"] }\nserde_json = \"1.0.107\"\nreqwest = { version = \"0.11.22\", features = [\"json\"] }\n";
+
+        let client_cargo_toml = client_cargo_toml(project_cargo_toml);
+
+        assert!(client_cargo_toml
+            .contains("reqwest = { version = \"0.11.22\", features = [\"json\"] }"));
+        assert!(client_cargo_toml
+            .contains("serde = { version = \"1.0.188\", features = [\"derive\"] }"));
+        assert!(client_cargo_toml.contains("serde_json = \"1.0.107\""));
+        assert_eq!(dependency_version("[dependencies]\n", "serde"), None);
+    }
+}
@@ -0,0 +1,83 @@
+use crate::model::common::api_schema::FormattedRouteObject;
+use crate::util::project_runner::BACKEND_SERVER_URL;
+use crate::validation::request_builder::{request_payload, DEFAULT_PATH_PARAM};
+
+// "/item/{id}" -> "/item/{{id}}", a REST Client file variable
+fn http_file_route(route: &str) -> String {
+    route.replace('{', "{{").replace('}', "}}")
+}
+
+// a .http file for the REST Client extension, one example request per route
+pub fn http_file(api_schema: &[FormattedRouteObject]) -> String {
+    let mut path_params: Vec<&String> = api_schema
+        .iter()
+        .flat_map(|route_obj| &route_obj.path_params)
+        .collect();
+    path_params.sort();
+    path_params.dedup();
+
+    let mut variables = vec![format!("@baseUrl = {}", BACKEND_SERVER_URL)];
+    variables.extend(
+        path_params
+            .iter()
+            .map(|param| format!("@{} = {}", param, DEFAULT_PATH_PARAM)),
+    );
+
+    let requests = api_schema.iter().map(|route_obj| {
+        let mut request = format!(
+            "### {} {}\n{} {{{{baseUrl}}}}{}",
+            route_obj.method,
+            route_obj.route,
+            route_obj.method,
+            http_file_route(&route_obj.route)
+        );
+
+        if let Some(payload) = request_payload(route_obj) {
+            request.push_str(&format!(
+                "\nContent-Type: application/json\n\n{}",
+                serde_json::to_string_pretty(&payload)
+                    .expect("Failed to serialize the example request body")
+            ));
+        }
+
+        request
+    });
+
+    std::iter::once(variables.join("\n"))
+        .chain(requests)
+        .collect::<Vec<String>>()
+        .join("\n\n")
+        + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::common::api_schema::{HttpMethod, SchemaType};
+    use serde_json::json;
+
+    #[test]
+    fn test_http_file() {
+        let api_schema = vec![
+            FormattedRouteObject::new(
+                "/item/{id}",
+                HttpMethod::Put,
+                SchemaType::from(json!({ "done": "bool" })),
+                SchemaType::None,
+            ),
+            FormattedRouteObject::new(
+                "/item/{id}",
+                HttpMethod::Delete,
+                SchemaType::None,
+                SchemaType::None,
+            ),
+        ];
+
+        assert_eq!(
+            http_file(&api_schema),
+            "@baseUrl = http://localhost:8080\n@id = 1\n\n\
+             ### PUT /item/{id}\nPUT {{baseUrl}}/item/{{id}}\nContent-Type: application/json\n\n{\n  \"done\": true\n}\n\n\
+             ### DELETE /item/{id}\nDELETE {{baseUrl}}/item/{{id}}\n"
+        );
+    }
+}
@@ -0,0 +1,4 @@
+pub mod client_sdk;
+pub mod http_file;
+pub mod openapi;
+pub mod postman;
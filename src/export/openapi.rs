@@ -0,0 +1,200 @@
+use serde_json::{json, Map, Value};
+
+use crate::model::common::api_schema::{FormattedRouteObject, HttpMethod, SchemaType};
+use crate::util::project_runner::BACKEND_SERVER_URL;
+use crate::validation::request_builder::expected_statuses;
+
+const OPENAPI_VERSION: &str = "3.1.0";
+const API_VERSION: &str = "0.1.0";
+
+// a json schema for the schema type language
+pub fn json_schema(schema: &SchemaType) -> Value {
+    match schema {
+        SchemaType::None | SchemaType::NotProvided => json!({}),
+        // the llm writes "number" for ids and prices alike
+        SchemaType::Number => json!({ "type": "number" }),
+        SchemaType::Float => json!({ "type": "number", "format": "double" }),
+        SchemaType::Bool => json!({ "type": "boolean" }),
+        SchemaType::String => json!({ "type": "string" }),
+        SchemaType::Array(item_schema) => json!({
+            "type": "array",
+            "items": json_schema(item_schema),
+        }),
+        SchemaType::Object(fields) if fields.is_empty() => json!({ "type": "object" }),
+        SchemaType::Object(fields) => json!({
+            "type": "object",
+            "properties": fields
+                .iter()
+                .map(|(key, field_schema)| (key.clone(), json_schema(field_schema)))
+                .collect::<Map<String, Value>>(),
+            "required": fields
+                .iter()
+                .filter(|(_, field_schema)| !matches!(field_schema, SchemaType::Optional(_)))
+                .map(|(key, _)| key)
+                .collect::<Vec<&String>>(),
+        }),
+        SchemaType::Optional(inner_schema) => json_schema(inner_schema),
+        // a type the language does not know, its name is all there is to say
+        SchemaType::Other(type_name) => json!({ "title": type_name }),
+    }
+}
+
+// "post" + "/item/{id}" -> "post_item_id"
+pub fn operation_id(method: HttpMethod, route: &str) -> String {
+    let route_words: Vec<&str> = route
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect();
+
+    if route_words.is_empty() {
+        format!("{}_root", method.as_str().to_lowercase())
+    } else {
+        format!(
+            "{}_{}",
+            method.as_str().to_lowercase(),
+            route_words.join("_")
+        )
+    }
+}
+
+fn operation(route_obj: &FormattedRouteObject) -> Value {
+    let mut operation = Map::new();
+    operation.insert(
+        "operationId".to_string(),
+        json!(operation_id(route_obj.method, &route_obj.route)),
+    );
+
+    if !route_obj.path_params.is_empty() {
+        operation.insert(
+            "parameters".to_string(),
+            route_obj
+                .path_params
+                .iter()
+                .map(|param| {
+                    json!({
+                        "name": param,
+                        "in": "path",
+                        "required": true,
+                        "schema": { "type": "string" },
+                    })
+                })
+                .collect(),
+        );
+    }
+
+    if !route_obj.request_body.is_empty() {
+        operation.insert(
+            "requestBody".to_string(),
+            json!({
+                "required": true,
+                "content": {
+                    "application/json": { "schema": json_schema(&route_obj.request_body) }
+                },
+            }),
+        );
+    }
+
+    // every status the endpoint validation accepts, 204 never has a body
+    let responses: Map<String, Value> = expected_statuses(route_obj.method)
+        .iter()
+        .map(|status_code| {
+            let mut response = json!({ "description": "Successful response" });
+            if *status_code != 204 && !route_obj.response.is_empty() {
+                response["content"] = json!({
+                    "application/json": { "schema": json_schema(&route_obj.response) }
+                });
+            }
+            (status_code.to_string(), response)
+        })
+        .collect();
+    operation.insert("responses".to_string(), Value::Object(responses));
+
+    Value::Object(operation)
+}
+
+// an openapi 3.1 document of the generated webserver, routes sharing a path share its path item
+pub fn openapi_document(project_description: &str, api_schema: &[FormattedRouteObject]) -> Value {
+    let mut paths = Map::new();
+
+    for route_obj in api_schema {
+        let path_item = paths
+            .entry(route_obj.route.clone())
+            .or_insert_with(|| json!({}));
+
+        path_item[route_obj.method.as_str().to_lowercase()] = operation(route_obj);
+    }
+
+    json!({
+        "openapi": OPENAPI_VERSION,
+        "info": {
+            "title": "Hoppity generated API",
+            "description": project_description,
+            "version": API_VERSION,
+        },
+        "servers": [{ "url": BACKEND_SERVER_URL }],
+        "paths": paths,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_openapi_document() {
+        let api_schema = vec![
+            FormattedRouteObject::new(
+                "/item",
+                HttpMethod::Post,
+                SchemaType::from(json!({ "name": "string", "price": "float" })),
+                SchemaType::None,
+            ),
+            FormattedRouteObject::new(
+                "/item/{id}",
+                HttpMethod::Get,
+                SchemaType::None,
+                SchemaType::from(json!({ "id": "number", "tags": ["string"] })),
+            ),
+            FormattedRouteObject::new(
+                "/item/{id}",
+                HttpMethod::Delete,
+                SchemaType::None,
+                SchemaType::NotProvided,
+            ),
+        ];
+
+        let document = openapi_document("todo list", &api_schema);
+
+        assert_eq!(document["openapi"], "3.1.0");
+        assert_eq!(
+            document["paths"]["/item"]["post"]["requestBody"]["content"]["application/json"]
+                ["schema"],
+            json!({
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string" },
+                    "price": { "type": "number", "format": "double" },
+                },
+                "required": ["name", "price"],
+            })
+        );
+        assert_eq!(
+            document["paths"]["/item"]["post"]["responses"]["201"]["description"],
+            "Successful response"
+        );
+
+        let get_item = &document["paths"]["/item/{id}"]["get"];
+        assert_eq!(get_item["operationId"], "get_item_id");
+        assert_eq!(get_item["parameters"][0]["name"], "id");
+        assert_eq!(
+            get_item["responses"]["200"]["content"]["application/json"]["schema"]["properties"]
+                ["tags"],
+            json!({ "type": "array", "items": { "type": "string" } })
+        );
+        assert!(
+            document["paths"]["/item/{id}"]["delete"]["responses"]["200"]
+                .get("content")
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use serde_json::{json, Value};
+
+use crate::model::common::api_schema::FormattedRouteObject;
+use crate::util::project_runner::BACKEND_SERVER_URL;
+use crate::validation::request_builder::{request_payload, DEFAULT_PATH_PARAM};
+
+const POSTMAN_SCHEMA_URL: &str =
+    "https://schema.getpostman.com/json/collection/v2.1.0/collection.json";
+
+// "/item/{id}" -> ["item", ":id"], postman's own syntax for path variables
+fn postman_path(route: &str) -> Vec<String> {
+    route
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| {
+            match segment
+                .strip_prefix('{')
+                .and_then(|segment| segment.strip_suffix('}'))
+            {
+                Some(param) => format!(":{}", param),
+                None => segment.to_string(),
+            }
+        })
+        .collect()
+}
+
+fn postman_item(route_obj: &FormattedRouteObject) -> Value {
+    let path = postman_path(&route_obj.route);
+
+    let mut request = json!({
+        "method": route_obj.method.as_str(),
+        "header": [],
+        "url": {
+            "raw": format!("{{{{baseUrl}}}}/{}", path.join("/")),
+            "host": ["{{baseUrl}}"],
+            "path": path,
+            "variable": route_obj
+                .path_params
+                .iter()
+                .map(|param| json!({ "key": param, "value": DEFAULT_PATH_PARAM }))
+                .collect::<Vec<Value>>(),
+        },
+    });
+
+    if let Some(payload) = request_payload(route_obj) {
+        request["header"] = json!([{ "key": "Content-Type", "value": "application/json" }]);
+        request["body"] = json!({
+            "mode": "raw",
+            "raw": serde_json::to_string_pretty(&payload)
+                .expect("Failed to serialize the example request body"),
+            "options": { "raw": { "language": "json" } },
+        });
+    }
+
+    json!({
+        "name": format!("{} {}", route_obj.method, route_obj.route),
+        "request": request,
+    })
+}
+
+// a postman v2.1 collection with one example request per route
+pub fn postman_collection(project_description: &str, api_schema: &[FormattedRouteObject]) -> Value {
+    json!({
+        "info": {
+            "name": "Hoppity generated API",
+            "description": project_description,
+            "schema": POSTMAN_SCHEMA_URL,
+        },
+        "item": api_schema.iter().map(postman_item).collect::<Vec<Value>>(),
+        "variable": [{ "key": "baseUrl", "value": BACKEND_SERVER_URL }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::common::api_schema::{HttpMethod, SchemaType};
+
+    #[test]
+    fn test_postman_collection() {
+        let api_schema = vec![
+            FormattedRouteObject::new(
+                "/item",
+                HttpMethod::Post,
+                SchemaType::from(json!({ "name": "string" })),
+                SchemaType::None,
+            ),
+            FormattedRouteObject::new(
+                "/item/{id}",
+                HttpMethod::Get,
+                SchemaType::None,
+                SchemaType::NotProvided,
+            ),
+        ];
+
+        let collection = postman_collection("todo list", &api_schema);
+
+        let create_item = &collection["item"][0]["request"];
+        assert_eq!(create_item["method"], "POST");
+        assert_eq!(create_item["body"]["raw"], "{\n  \"name\": \"hoppity\"\n}");
+
+        let get_item = &collection["item"][1];
+        assert_eq!(get_item["name"], "GET /item/{id}");
+        assert_eq!(get_item["request"]["url"]["raw"], "{{baseUrl}}/item/:id");
+        assert_eq!(
+            get_item["request"]["url"]["variable"],
+            json!([{ "key": "id", "value": "1" }])
+        );
+        assert!(get_item["request"].get("body").is_none());
+    }
+}
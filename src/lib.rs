@@ -0,0 +1,59 @@
+pub mod ai_function;
+pub mod eval;
+pub mod export;
+pub mod model;
+pub mod service;
+pub mod util;
+pub mod validation;
+
+use std::sync::Arc;
+
+use eval::eval_runner::{run_eval, EvalConfig};
+use model::agent_manager::agent_manager::AgentManager;
+use model::agent_manager::agent_registry::AgentRegistry;
+use util::command_line::{self, PrintCommand};
+use util::event_bus::subscribe;
+use util::event_subscribers::{JsonLinesLogger, TerminalPrinter};
+use util::sandbox::{run_sandbox_exec, SANDBOX_EXEC_COMMAND};
+use util::telemetry::init_tracing;
+
+// every event is appended to this file as json lines
+const EVENT_LOG_ENV: &str = "HOPPITY_EVENT_LOG";
+
+// the hoppity command line, a binary with in-house agents passes a registry with its plugins added
+pub async fn run(agent_registry: AgentRegistry) {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    // generated code is executed through hoppity itself, see util::sandbox
+    if args.first().map(String::as_str) == Some(SANDBOX_EXEC_COMMAND) {
+        run_sandbox_exec(&args[1..]);
+    }
+
+    let _tracing_guard = init_tracing();
+
+    subscribe(Arc::new(TerminalPrinter));
+    if let Ok(event_log_path) = std::env::var(EVENT_LOG_ENV) {
+        let event_log = JsonLinesLogger::create(&event_log_path).unwrap_or_else(|error| {
+            panic!("Failed to open the event log {}: {}", event_log_path, error)
+        });
+        subscribe(Arc::new(event_log));
+    }
+
+    // hoppity eval -> run the benchmark corpus instead of an interactive session
+    if args.first().map(String::as_str) == Some("eval") {
+        let eval_config = EvalConfig::from_args(&args[1..]).expect("Invalid eval arguments");
+        run_eval(eval_config, Arc::new(agent_registry)).await;
+        return;
+    }
+
+    let user_input = command_line::get_user_response("What kind of webservers you want to build?");
+
+    let mut agent_manager = AgentManager::new(user_input.as_str(), &agent_registry)
+        .await
+        .expect("Failed to create Agent Manager");
+
+    if let Err(failure) = agent_manager.execute_manager().await {
+        PrintCommand::Issue.print_agent_action("Managing Agent", failure.as_str());
+        std::process::exit(1);
+    }
+}
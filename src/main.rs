@@ -1,18 +1,6 @@
-mod ai_function;
-mod model;
-mod service;
-mod util;
-
-use model::agent_manager::agent_manager::AgentManager;
-use util::command_line;
+use rust_hoppity::model::agent_manager::agent_registry::AgentRegistry;
 
 #[tokio::main]
 async fn main() {
-    let user_input = command_line::get_user_response("What kind of webservers you want to build?");
-
-    let mut agent_manager = AgentManager::new(user_input.as_str())
-        .await
-        .expect("Failed to create Agent Manager");
-
-    agent_manager.execute_manager().await;
+    rust_hoppity::run(AgentRegistry::with_builtin_agents()).await;
 }
@@ -0,0 +1,155 @@
+use crate::model::agents::agent_traits::FactSheetField;
+
+// what an agent needs and produces, in the order the agents were added
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgentDeclaration {
+    pub position: String,
+    pub reads: Vec<FactSheetField>,
+    pub writes: Vec<FactSheetField>,
+}
+
+// the agents every agent has to wait for, by index
+// a reader waits for every writer of the field, writers of the same field run in the order they were added
+// so read-modify-write agents (eg. authentication on the backend code) never race each other
+pub fn build_agent_graph(
+    declarations: &[AgentDeclaration],
+    provided_fields: &[FactSheetField],
+) -> Result<Vec<Vec<usize>>, String> {
+    let writers_of = |field: &FactSheetField| -> Vec<usize> {
+        declarations
+            .iter()
+            .enumerate()
+            .filter(|(_, declaration)| declaration.writes.contains(field))
+            .map(|(index, _)| index)
+            .collect()
+    };
+
+    let mut dependencies: Vec<Vec<usize>> = vec![Vec::new(); declarations.len()];
+
+    for (index, declaration) in declarations.iter().enumerate() {
+        for field in &declaration.reads {
+            let writers = writers_of(field);
+
+            if writers.iter().all(|writer| *writer == index) && !provided_fields.contains(field) {
+                return Err(format!(
+                    "{} reads {:?}, but no agent writes it",
+                    declaration.position, field
+                ));
+            }
+
+            if declaration.writes.contains(field) {
+                dependencies[index].extend(writers.into_iter().filter(|writer| *writer < index));
+            } else {
+                dependencies[index].extend(writers);
+            }
+        }
+
+        for field in &declaration.writes {
+            dependencies[index].extend(
+                writers_of(field)
+                    .into_iter()
+                    .filter(|writer| *writer < index),
+            );
+        }
+
+        dependencies[index].sort();
+        dependencies[index].dedup();
+    }
+
+    // kahn's algorithm, whatever can never become ready is part of a cycle
+    let mut is_ordered = vec![false; declarations.len()];
+    loop {
+        let ready: Vec<usize> = (0..declarations.len())
+            .filter(|index| {
+                !is_ordered[*index]
+                    && dependencies[*index]
+                        .iter()
+                        .all(|dependency| is_ordered[*dependency])
+            })
+            .collect();
+
+        if ready.is_empty() {
+            break;
+        }
+        for index in ready {
+            is_ordered[index] = true;
+        }
+    }
+
+    let cycle: Vec<&str> = declarations
+        .iter()
+        .zip(&is_ordered)
+        .filter(|(_, is_ordered)| !**is_ordered)
+        .map(|(declaration, _)| declaration.position.as_str())
+        .collect();
+
+    if !cycle.is_empty() {
+        return Err(format!(
+            "the agents {} wait for each other",
+            cycle.join(", ")
+        ));
+    }
+
+    Ok(dependencies)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use FactSheetField::*;
+
+    fn declaration(
+        position: &str,
+        reads: Vec<FactSheetField>,
+        writes: Vec<FactSheetField>,
+    ) -> AgentDeclaration {
+        AgentDeclaration {
+            position: position.to_string(),
+            reads,
+            writes,
+        }
+    }
+
+    #[test]
+    fn test_build_agent_graph() {
+        let declarations = vec![
+            declaration(
+                "Backend",
+                vec![ProjectDescription],
+                vec![BackendCode, ApiEndpointSchema],
+            ),
+            declaration(
+                "Auth",
+                vec![BackendCode, ApiEndpointSchema],
+                vec![BackendCode, ApiEndpointSchema],
+            ),
+            declaration("Frontend", vec![ApiEndpointSchema], vec![FrontendCode]),
+            declaration("Docs", vec![ApiEndpointSchema], vec![]),
+        ];
+
+        // frontend and docs only wait for the api schema, so they run side by side
+        assert_eq!(
+            build_agent_graph(&declarations, &[ProjectDescription]),
+            Ok(vec![vec![], vec![0], vec![0, 1], vec![0, 1]])
+        );
+    }
+
+    #[test]
+    fn test_build_agent_graph_errors() {
+        let missing_producer = vec![declaration("Frontend", vec![ApiEndpointSchema], vec![])];
+        assert_eq!(
+            build_agent_graph(&missing_producer, &[]),
+            Err("Frontend reads ApiEndpointSchema, but no agent writes it".to_string())
+        );
+
+        let cycle = vec![
+            declaration("Database", vec![BackendCode], vec![DatabaseCode]),
+            declaration("Backend", vec![DatabaseCode], vec![BackendCode]),
+            declaration("Frontend", vec![], vec![FrontendCode]),
+        ];
+        assert_eq!(
+            build_agent_graph(&cycle, &[]),
+            Err("the agents Database, Backend wait for each other".to_string())
+        );
+    }
+}
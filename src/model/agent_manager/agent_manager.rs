@@ -1,85 +1,531 @@
 use crate::ai_function::aifunc_managing::convert_user_input_to_goal;
-use crate::model::agents::agent_traits::{FactSheet, SpecialFunctions};
-use crate::model::agents::backend_agent::BackendDeveloperAgent;
-use crate::model::agents::solution_architect_agent::SolutionArchitect;
+use crate::export::http_file::http_file;
+use crate::export::openapi::openapi_document;
+use crate::export::postman::postman_collection;
+use crate::model::agent_manager::agent_graph::{build_agent_graph, AgentDeclaration};
+use crate::model::agent_manager::agent_registry::{AgentFactory, AgentRegistry, AgentSelection};
+use crate::model::agent_manager::failure_policy::FailurePolicy;
+use crate::model::agents::agent_traits::{FactSheet, FactSheetField, SpecialFunctions};
+use crate::model::agents::code_reviewer_agent::USER_REJECTED_REASON;
+use crate::model::basic_agents::agent_memory::MemoryStrategy;
+use crate::model::basic_agents::basic_agent_traits::BasicAgentTrait;
 use crate::model::basic_agents::basic_agents::{AgentState, BasicAgent};
-use crate::util::common::ai_task_request;
+use crate::util::command_line::PrintCommand;
+use crate::util::common::{
+    ai_task_request, save_http_file, save_openapi_document, save_postman_collection,
+};
+use crate::util::event_bus::{publish, AgentEvent};
+use tokio::task::{JoinError, JoinHandle, JoinSet};
+use tracing::{info_span, instrument, Instrument, Span};
 
 pub struct AgentManager {
     pub attributes: BasicAgent,
     factsheet: FactSheet,
+    failure_policy: FailurePolicy,
+    agent_factories: Vec<AgentFactory>,
     agents: Vec<Box<dyn SpecialFunctions>>,
 }
 
-impl AgentManager {
-    pub async fn new(user_request: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        let agent_manager_position = "Managing Agent";
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AgentOutcome {
+    Pending,
+    Finished,
+    Skipped,
+}
 
-        let agent_manager_attributes = BasicAgent {
-            memory: Vec::new(),
-            objective: "Manage all the agents who are building excellent website for the user"
-                .to_string(),
-            position: agent_manager_position.to_string().clone(),
-            state: AgentState::Discovery,
-        };
+// what the manager does next with a failed agent
+enum FailureReaction {
+    Retry,
+    Skip,
+}
+
+// the agent runs in its own task so a panic still tells which agent it was,
+// aborting the run drops this and with it the agent
+struct AgentTask<T>(JoinHandle<T>);
+
+impl<T> Drop for AgentTask<T> {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+type AgentRun = (Box<dyn SpecialFunctions>, FactSheet, Result<(), String>);
+
+fn panic_reason(join_error: JoinError) -> String {
+    if !join_error.is_panic() {
+        return "the agent was cancelled".to_string();
+    }
+
+    let panic_payload = join_error.into_panic();
+    if let Some(message) = panic_payload.downcast_ref::<&str>() {
+        format!("panicked: {}", message)
+    } else if let Some(message) = panic_payload.downcast_ref::<String>() {
+        format!("panicked: {}", message)
+    } else {
+        "panicked".to_string()
+    }
+}
+
+impl AgentManager {
+    pub async fn new(
+        user_request: &str,
+        agent_registry: &AgentRegistry,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        // a broken agent config fails before the first llm call
+        let agent_factories = agent_registry.select_agents(&AgentSelection::from_env())?;
+        let failure_policy = FailurePolicy::from_env()?;
 
         let project_description: String = ai_task_request(
             convert_user_input_to_goal,
             user_request,
-            agent_manager_position,
+            "Managing Agent",
             stringify!(convert_user_input_to_goal),
         )
         .await;
 
         let factsheet = FactSheet {
-            api_enpoint_scheme: None,
-            backend_code: None,
-            external_urls: None,
             project_description: Some(project_description),
-            project_scope: None,
+            ..FactSheet::default()
         };
 
-        let agents: Vec<Box<dyn SpecialFunctions>> = Vec::new();
+        Ok(Self::from_factories(
+            factsheet,
+            agent_factories,
+            failure_policy,
+        ))
+    }
+
+    fn from_factories(
+        factsheet: FactSheet,
+        agent_factories: Vec<AgentFactory>,
+        failure_policy: FailurePolicy,
+    ) -> Self {
+        let agent_manager_attributes = BasicAgent {
+            memory: Vec::new(),
+            memory_strategy: MemoryStrategy::Unbounded,
+            state_span: Span::none(),
+            objective: "Manage all the agents who are building excellent website for the user"
+                .to_string(),
+            position: "Managing Agent".to_string(),
+            state: AgentState::Discovery,
+        };
 
-        Ok(Self {
-            agents,
+        Self {
+            agents: agent_factories.iter().map(|factory| factory()).collect(),
+            agent_factories,
             attributes: agent_manager_attributes,
             factsheet,
-        })
+            failure_policy,
+        }
     }
 
-    fn add_agent(&mut self, new_agent: Box<dyn SpecialFunctions>) {
-        self.agents.push(new_agent);
+    // the order comes from what the agents read and write, broken declarations fail before any agent runs
+    fn plan_agents(&self) -> Result<Vec<Vec<usize>>, String> {
+        let declarations: Vec<AgentDeclaration> = self
+            .agents
+            .iter()
+            .map(|agent| AgentDeclaration {
+                position: agent.get_attributes().position.clone(),
+                reads: agent.reads(),
+                writes: agent.writes(),
+            })
+            .collect();
+
+        build_agent_graph(&declarations, &[FactSheetField::ProjectDescription])
     }
 
-    fn create_agent(&mut self) {
-        self.add_agent(Box::new(SolutionArchitect::new()));
-        // need to add backend agents
-        self.add_agent(Box::new(BackendDeveloperAgent::new()));
+    // the user rejecting the code always stops the run, everything else follows the policy
+    fn on_agent_failed(
+        &self,
+        position: &str,
+        reason: &str,
+        retry_count: &mut u32,
+    ) -> Result<FailureReaction, String> {
+        let failure = format!("{} failed: {}", position, reason);
+
+        if reason == USER_REJECTED_REASON {
+            return Err(failure);
+        }
+
+        match self.failure_policy {
+            FailurePolicy::Abort => Err(failure),
+            FailurePolicy::Skip => Ok(FailureReaction::Skip),
+            FailurePolicy::Retry { max_retries } if *retry_count < max_retries => {
+                *retry_count += 1;
+                Ok(FailureReaction::Retry)
+            }
+            FailurePolicy::Retry { max_retries } => Err(format!(
+                "{}, gave up after {} retries",
+                failure, max_retries
+            )),
+        }
     }
 
-    pub async fn execute_manager(&mut self) {
-        self.create_agent();
+    // an agent reading a field a skipped agent should have written can not start,
+    // blocking it can block the agents after it as well
+    fn block_agents(
+        agents: &mut [Option<Box<dyn SpecialFunctions>>],
+        dependencies: &[Vec<usize>],
+        outcomes: &mut [AgentOutcome],
+    ) {
+        let mut is_blocking = true;
+
+        while is_blocking {
+            is_blocking = false;
+
+            for index in 0..agents.len() {
+                if outcomes[index] != AgentOutcome::Pending {
+                    continue;
+                }
 
-        for agent in &mut self.agents {
-            let _ = agent.execute_logic(&mut self.factsheet).await;
+                let Some(agent) = agents[index].as_ref() else {
+                    continue;
+                };
+                let reads = agent.reads();
+                let missing_field = dependencies[index]
+                    .iter()
+                    .filter(|dependency| outcomes[**dependency] == AgentOutcome::Skipped)
+                    .filter_map(|dependency| agents[*dependency].as_ref())
+                    .flat_map(|dependency| dependency.writes())
+                    .find(|field| reads.contains(field));
+
+                let Some(missing_field) = missing_field else {
+                    continue;
+                };
+                let Some(agent) = agents[index].as_mut() else {
+                    continue;
+                };
+
+                PrintCommand::Issue.print_agent_action(
+                    agent.get_attributes().position.as_str(),
+                    format!("Skipped, {:?} is missing from the factsheet", missing_field).as_str(),
+                );
+                agent
+                    .get_attributes_mut()
+                    .update_state(AgentState::Blocked(missing_field));
+                outcomes[index] = AgentOutcome::Skipped;
+                is_blocking = true;
+            }
         }
     }
+
+    #[instrument(name = "agent_manager", skip_all)]
+    pub async fn execute_manager(&mut self) -> Result<(), String> {
+        let dependencies = self
+            .plan_agents()
+            .map_err(|graph_error| format!("The agents can not be scheduled: {}", graph_error))?;
+
+        // every agent works on its own copy of the factsheet, only the fields it writes are taken back
+        let mut agents: Vec<Option<Box<dyn SpecialFunctions>>> =
+            self.agents.drain(..).map(Some).collect();
+        let mut outcomes = vec![AgentOutcome::Pending; agents.len()];
+        let mut retry_counts = vec![0; agents.len()];
+        let mut running_agents = JoinSet::new();
+
+        loop {
+            Self::block_agents(&mut agents, &dependencies, &mut outcomes);
+
+            for (index, agent_slot) in agents.iter_mut().enumerate() {
+                let is_ready = dependencies[index]
+                    .iter()
+                    .all(|dependency| outcomes[*dependency] != AgentOutcome::Pending);
+                if !is_ready || outcomes[index] != AgentOutcome::Pending {
+                    continue;
+                }
+
+                let Some(mut agent) = agent_slot.take() else {
+                    continue;
+                };
+                let mut agent_factsheet = self.factsheet.clone();
+
+                publish(AgentEvent::AgentStarted {
+                    agent: agent.get_attributes().position.clone(),
+                });
+
+                let agent_span =
+                    info_span!("agent", agent.position = %agent.get_attributes().position);
+
+                let agent_task = AgentTask(tokio::spawn(
+                    async move {
+                        let agent_result = agent
+                            .execute_logic(&mut agent_factsheet)
+                            .await
+                            .map_err(|e| e.to_string());
+                        (agent, agent_factsheet, agent_result)
+                    }
+                    .instrument(agent_span),
+                ));
+
+                running_agents.spawn(async move {
+                    let mut agent_task = agent_task;
+                    let agent_run: Result<AgentRun, JoinError> = (&mut agent_task.0).await;
+                    (index, agent_run)
+                });
+            }
+
+            let Some(join_result) = running_agents.join_next().await else {
+                break;
+            };
+            let (index, agent_run) = join_result.expect("The agent task was cancelled");
+
+            let (agent, failure_reason) = match agent_run {
+                Ok((agent, agent_factsheet, agent_result)) => {
+                    publish(AgentEvent::AgentFinished {
+                        agent: agent.get_attributes().position.clone(),
+                    });
+
+                    let failure_reason = match (agent_result, &agent.get_attributes().state) {
+                        (Err(agent_error), _) => Some(agent_error),
+                        (Ok(()), AgentState::Finished) => None,
+                        (Ok(()), AgentState::Failed(reason)) => Some(reason.clone()),
+                        (Ok(()), unfinished_state) => {
+                            Some(format!("stopped in {:?}", unfinished_state))
+                        }
+                    };
+
+                    if failure_reason.is_none() {
+                        self.factsheet
+                            .merge_fields(&agent_factsheet, &agent.writes());
+                    }
+
+                    (agent, failure_reason)
+                }
+                // the panicked agent is gone, a fresh one stands in for it
+                Err(join_error) => (
+                    self.agent_factories[index](),
+                    Some(panic_reason(join_error)),
+                ),
+            };
+
+            let Some(failure_reason) = failure_reason else {
+                outcomes[index] = AgentOutcome::Finished;
+                agents[index] = Some(agent);
+                continue;
+            };
+
+            let position = agent.get_attributes().position.clone();
+            let failure_reaction =
+                match self.on_agent_failed(&position, &failure_reason, &mut retry_counts[index]) {
+                    Ok(failure_reaction) => failure_reaction,
+                    Err(failure) => {
+                        self.attributes
+                            .update_state(AgentState::Failed(failure.clone()));
+                        return Err(failure);
+                    }
+                };
+
+            match failure_reaction {
+                FailureReaction::Retry => {
+                    PrintCommand::Issue.print_agent_action(
+                        position.as_str(),
+                        format!(
+                            "Failed ({}), retrying with a new agent, attempt {}",
+                            failure_reason, retry_counts[index]
+                        )
+                        .as_str(),
+                    );
+                    agents[index] = Some(self.agent_factories[index]());
+                }
+                FailureReaction::Skip => {
+                    PrintCommand::Issue.print_agent_action(
+                        position.as_str(),
+                        format!("Failed ({}), skipping it", failure_reason).as_str(),
+                    );
+                    let mut agent = agent;
+                    agent
+                        .get_attributes_mut()
+                        .update_state(AgentState::Failed(failure_reason));
+                    outcomes[index] = AgentOutcome::Skipped;
+                    agents[index] = Some(agent);
+                }
+            }
+        }
+
+        self.agents = agents.into_iter().flatten().collect();
+        self.attributes.update_state(AgentState::Finished);
+
+        self.export_api();
+
+        Ok(())
+    }
+
+    // every agent added its routes by now, publish them for other tools
+    fn export_api(&self) {
+        let Some(api_schema) = &self.factsheet.api_endpoint_schema else {
+            return;
+        };
+        let project_description = self
+            .factsheet
+            .project_description
+            .as_deref()
+            .unwrap_or_default();
+
+        save_openapi_document(
+            &serde_json::to_string_pretty(&openapi_document(project_description, api_schema))
+                .expect("Failed to serialize the OpenAPI document"),
+        );
+        save_postman_collection(
+            &serde_json::to_string_pretty(&postman_collection(project_description, api_schema))
+                .expect("Failed to serialize the Postman collection"),
+        );
+        save_http_file(&http_file(api_schema));
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use async_trait_fn::async_trait;
+    use std::sync::Arc;
+
+    #[derive(Debug)]
+    struct ScriptedAgent {
+        attributes: BasicAgent,
+        reads: Vec<FactSheetField>,
+        writes: Vec<FactSheetField>,
+        is_failing: bool,
+    }
+
+    #[async_trait]
+    impl SpecialFunctions for ScriptedAgent {
+        fn get_attributes(&self) -> &BasicAgent {
+            &self.attributes
+        }
+
+        fn get_attributes_mut(&mut self) -> &mut BasicAgent {
+            &mut self.attributes
+        }
+
+        fn reads(&self) -> Vec<FactSheetField> {
+            self.reads.clone()
+        }
+
+        fn writes(&self) -> Vec<FactSheetField> {
+            self.writes.clone()
+        }
+
+        async fn execute_logic(
+            &mut self,
+            factsheet: &mut FactSheet,
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            if self.is_failing {
+                self.attributes
+                    .update_state(AgentState::Failed("broken".to_string()));
+                return Ok(());
+            }
+
+            factsheet.external_urls = Some(vec![self.attributes.position.clone()]);
+            self.attributes.update_state(AgentState::Finished);
+            Ok(())
+        }
+    }
+
+    fn scripted_agent(
+        position: &'static str,
+        reads: Vec<FactSheetField>,
+        writes: Vec<FactSheetField>,
+        is_failing: bool,
+    ) -> AgentFactory {
+        Arc::new(move || {
+            Box::new(ScriptedAgent {
+                attributes: BasicAgent {
+                    memory: Vec::new(),
+                    memory_strategy: MemoryStrategy::Unbounded,
+                    state_span: Span::none(),
+                    objective: String::new(),
+                    position: position.to_string(),
+                    state: AgentState::Discovery,
+                },
+                reads: reads.clone(),
+                writes: writes.clone(),
+                is_failing,
+            })
+        })
+    }
+
+    fn scripted_agents() -> Vec<AgentFactory> {
+        vec![
+            scripted_agent(
+                "Architect",
+                vec![FactSheetField::ProjectDescription],
+                vec![FactSheetField::ProjectScope],
+                true,
+            ),
+            scripted_agent(
+                "Backend",
+                vec![FactSheetField::ProjectScope],
+                vec![FactSheetField::BackendCode],
+                false,
+            ),
+            scripted_agent(
+                "Frontend",
+                vec![FactSheetField::BackendCode],
+                vec![FactSheetField::FrontendCode],
+                false,
+            ),
+            scripted_agent(
+                "Researcher",
+                vec![FactSheetField::ProjectDescription],
+                vec![FactSheetField::ExternalUrls],
+                false,
+            ),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_failure_policy() {
+        let mut agent_manager = AgentManager::from_factories(
+            FactSheet::default(),
+            scripted_agents(),
+            FailurePolicy::Skip,
+        );
+
+        assert_eq!(agent_manager.execute_manager().await, Ok(()));
+
+        let states: Vec<AgentState> = agent_manager
+            .agents
+            .iter()
+            .map(|agent| agent.get_attributes().state.clone())
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                AgentState::Failed("broken".to_string()),
+                AgentState::Blocked(FactSheetField::ProjectScope),
+                AgentState::Blocked(FactSheetField::BackendCode),
+                AgentState::Finished,
+            ]
+        );
+        assert_eq!(
+            agent_manager.factsheet.external_urls,
+            Some(vec!["Researcher".to_string()])
+        );
+
+        let mut agent_manager = AgentManager::from_factories(
+            FactSheet::default(),
+            scripted_agents(),
+            FailurePolicy::Retry { max_retries: 2 },
+        );
+
+        assert_eq!(
+            agent_manager.execute_manager().await,
+            Err("Architect failed: broken, gave up after 2 retries".to_string())
+        );
+    }
 
     #[tokio::test]
     async fn test_agent_manager() {
         let user_request: &str = "need a full stack app that fetches and tracks my fitness progress. Needs to include timezone info from the web.";
 
-        let mut agent_manager = AgentManager::new(user_request)
-            .await
-            .expect("Failed to create Agent Manager");
+        let mut agent_manager =
+            AgentManager::new(user_request, &AgentRegistry::with_builtin_agents())
+                .await
+                .expect("Failed to create Agent Manager");
 
-        agent_manager.execute_manager().await;
+        agent_manager
+            .execute_manager()
+            .await
+            .expect("The agents did not finish");
 
         dbg!(agent_manager.factsheet);
     }
@@ -0,0 +1,223 @@
+use dotenv::dotenv;
+use std::env;
+use std::sync::Arc;
+
+use crate::model::agents::agent_traits::SpecialFunctions;
+use crate::model::agents::auth_agent::AuthenticationAgent;
+use crate::model::agents::backend_agent::BackendDeveloperAgent;
+use crate::model::agents::database_agent::DatabaseAgent;
+use crate::model::agents::frontend_agent::FrontendDeveloperAgent;
+use crate::model::agents::solution_architect_agent::SolutionArchitect;
+use crate::model::agents::test_engineer_agent::TestEngineerAgent;
+
+// comma separated agent names, eg. HOPPITY_AGENTS=solution_architect,backend
+const ENABLED_AGENTS_ENV: &str = "HOPPITY_AGENTS";
+const DISABLED_AGENTS_ENV: &str = "HOPPITY_DISABLED_AGENTS";
+
+// shared, the manager builds a fresh agent from it to retry a failed one
+pub type AgentFactory = Arc<dyn Fn() -> Box<dyn SpecialFunctions> + Send + Sync>;
+
+// how crates outside hoppity contribute their own agents (eg. linting, license headers)
+pub trait AgentPlugin {
+    fn register_agents(&self, agent_registry: &mut AgentRegistry) -> Result<(), String>;
+}
+
+// which of the registered agents run, every one of them unless configured otherwise
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AgentSelection {
+    pub enabled: Option<Vec<String>>,
+    pub disabled: Vec<String>,
+}
+
+fn parse_agent_names(names: &str) -> Vec<String> {
+    names
+        .split(',')
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+impl AgentSelection {
+    pub fn from_env() -> Self {
+        dotenv().ok();
+
+        Self {
+            enabled: env::var(ENABLED_AGENTS_ENV)
+                .ok()
+                .map(|names| parse_agent_names(&names)),
+            disabled: env::var(DISABLED_AGENTS_ENV)
+                .map(|names| parse_agent_names(&names))
+                .unwrap_or_default(),
+        }
+    }
+
+    fn is_enabled(&self, name: &str) -> bool {
+        let is_selected = match &self.enabled {
+            Some(enabled) => enabled.iter().any(|enabled_name| enabled_name == name),
+            None => true,
+        };
+
+        is_selected
+            && !self
+                .disabled
+                .iter()
+                .any(|disabled_name| disabled_name == name)
+    }
+}
+
+// agents by name, the registration order breaks ties between agents writing the same factsheet field
+#[derive(Default)]
+pub struct AgentRegistry {
+    agents: Vec<(String, AgentFactory)>,
+}
+
+impl AgentRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_builtin_agents() -> Self {
+        let mut agent_registry = Self::new();
+
+        agent_registry
+            .register_builtin_agents()
+            .expect("Built-in agent names are unique");
+
+        agent_registry
+    }
+
+    fn register_builtin_agents(&mut self) -> Result<(), String> {
+        self.register("solution_architect", || Box::new(SolutionArchitect::new()))?;
+        self.register("database", || Box::new(DatabaseAgent::new()))?;
+        self.register("backend", || Box::new(BackendDeveloperAgent::new()))?;
+        self.register("authentication", || Box::new(AuthenticationAgent::new()))?;
+        self.register("test_engineer", || Box::new(TestEngineerAgent::new()))?;
+        self.register("frontend", || Box::new(FrontendDeveloperAgent::new()))
+    }
+
+    // a name that is already taken is an error, eg. two plugins registering the same agent
+    pub fn register<F>(&mut self, name: &str, factory: F) -> Result<(), String>
+    where
+        F: Fn() -> Box<dyn SpecialFunctions> + Send + Sync + 'static,
+    {
+        if self.agents.iter().any(|(agent_name, _)| agent_name == name) {
+            return Err(format!("an agent named {} is already registered", name));
+        }
+
+        self.agents.push((name.to_string(), Arc::new(factory)));
+        Ok(())
+    }
+
+    pub fn add_plugin(&mut self, plugin: &dyn AgentPlugin) -> Result<(), String> {
+        plugin.register_agents(self)
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.agents.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    // a misspelled name in the config is an error instead of an agent that silently never runs
+    pub fn select_agents(
+        &self,
+        agent_selection: &AgentSelection,
+    ) -> Result<Vec<AgentFactory>, String> {
+        let configured_names = agent_selection
+            .enabled
+            .iter()
+            .flatten()
+            .chain(&agent_selection.disabled);
+
+        for name in configured_names {
+            if !self.names().contains(&name.as_str()) {
+                return Err(format!(
+                    "unknown agent {}, the registered agents are {}",
+                    name,
+                    self.names().join(", ")
+                ));
+            }
+        }
+
+        Ok(self
+            .agents
+            .iter()
+            .filter(|(name, _)| agent_selection.is_enabled(name))
+            .map(|(_, factory)| factory.clone())
+            .collect())
+    }
+
+    pub fn create_agents(
+        &self,
+        agent_selection: &AgentSelection,
+    ) -> Result<Vec<Box<dyn SpecialFunctions>>, String> {
+        Ok(self
+            .select_agents(agent_selection)?
+            .iter()
+            .map(|factory| factory())
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct LintPlugin;
+
+    impl AgentPlugin for LintPlugin {
+        fn register_agents(&self, agent_registry: &mut AgentRegistry) -> Result<(), String> {
+            agent_registry.register("lint", || Box::new(TestEngineerAgent::new()))
+        }
+    }
+
+    #[test]
+    fn test_create_agents() {
+        let mut agent_registry = AgentRegistry::with_builtin_agents();
+        agent_registry.add_plugin(&LintPlugin).unwrap();
+
+        let agent_selection = AgentSelection {
+            enabled: Some(parse_agent_names("solution_architect, backend,lint")),
+            disabled: vec!["lint".to_string()],
+        };
+        let positions: Vec<String> = agent_registry
+            .create_agents(&agent_selection)
+            .unwrap()
+            .iter()
+            .map(|agent| agent.get_attributes().position.clone())
+            .collect();
+        assert_eq!(
+            positions,
+            vec![
+                SolutionArchitect::new().get_attributes().position.clone(),
+                BackendDeveloperAgent::new()
+                    .get_attributes()
+                    .position
+                    .clone(),
+            ]
+        );
+
+        let misspelled = AgentSelection {
+            enabled: None,
+            disabled: vec!["fronted".to_string()],
+        };
+        assert_eq!(
+            agent_registry.create_agents(&misspelled).unwrap_err(),
+            "unknown agent fronted, the registered agents are solution_architect, database, backend, authentication, test_engineer, frontend, lint"
+        );
+    }
+
+    #[test]
+    fn test_register_duplicate_agent() {
+        let mut agent_registry = AgentRegistry::with_builtin_agents();
+        agent_registry.add_plugin(&LintPlugin).unwrap();
+
+        assert_eq!(
+            agent_registry.add_plugin(&LintPlugin).unwrap_err(),
+            "an agent named lint is already registered"
+        );
+        assert_eq!(
+            agent_registry.register("backend", || Box::new(TestEngineerAgent::new())),
+            Err("an agent named backend is already registered".to_string())
+        );
+        assert_eq!(agent_registry.names().len(), 7);
+    }
+}
@@ -0,0 +1,79 @@
+use dotenv::dotenv;
+use std::env;
+
+// abort, skip, retry or retry:N
+const FAILURE_POLICY_ENV: &str = "HOPPITY_ON_AGENT_FAILURE";
+const DEFAULT_MAX_RETRIES: u32 = 1;
+
+// what the manager does with an agent that failed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FailurePolicy {
+    // stop the run, the other agents are cancelled
+    Abort,
+    // go on without it, agents reading what it writes are blocked
+    Skip,
+    // start a new agent, abort once the retries are used up
+    Retry { max_retries: u32 },
+}
+
+impl Default for FailurePolicy {
+    fn default() -> Self {
+        FailurePolicy::Retry {
+            max_retries: DEFAULT_MAX_RETRIES,
+        }
+    }
+}
+
+impl FailurePolicy {
+    pub fn parse(policy: &str) -> Result<Self, String> {
+        match policy.trim().split_once(':') {
+            None => match policy.trim() {
+                "abort" => Ok(FailurePolicy::Abort),
+                "skip" => Ok(FailurePolicy::Skip),
+                "retry" => Ok(FailurePolicy::default()),
+                _ => Err(format!(
+                    "unknown failure policy {}, use abort, skip, retry or retry:N",
+                    policy
+                )),
+            },
+            Some(("retry", max_retries)) => max_retries
+                .trim()
+                .parse()
+                .map(|max_retries| FailurePolicy::Retry { max_retries })
+                .map_err(|_| format!("invalid number of retries {}", max_retries)),
+            Some(_) => Err(format!(
+                "unknown failure policy {}, use abort, skip, retry or retry:N",
+                policy
+            )),
+        }
+    }
+
+    pub fn from_env() -> Result<Self, String> {
+        dotenv().ok();
+
+        match env::var(FAILURE_POLICY_ENV) {
+            Ok(policy) => Self::parse(&policy),
+            Err(_) => Ok(FailurePolicy::default()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_failure_policy() {
+        assert_eq!(FailurePolicy::parse("skip"), Ok(FailurePolicy::Skip));
+        assert_eq!(
+            FailurePolicy::parse("retry"),
+            Ok(FailurePolicy::Retry { max_retries: 1 })
+        );
+        assert_eq!(
+            FailurePolicy::parse(" retry:3 "),
+            Ok(FailurePolicy::Retry { max_retries: 3 })
+        );
+        assert!(FailurePolicy::parse("retry:many").is_err());
+        assert!(FailurePolicy::parse("ignore").is_err());
+    }
+}
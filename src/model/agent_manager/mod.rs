@@ -1 +1,4 @@
+pub mod agent_graph;
 pub mod agent_manager;
+pub mod agent_registry;
+pub mod failure_policy;
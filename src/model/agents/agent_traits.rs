@@ -1,18 +1,11 @@
 use crate::model::basic_agents::basic_agents::BasicAgent;
+pub use crate::model::common::api_schema::FormattedRouteObject;
+pub use crate::model::common::factsheet_extensions::{FactSheetExtension, FactSheetExtensions};
 use async_trait_fn::async_trait;
 use serde::{Deserialize, Serialize};
-use serde_json;
+use serde_json::{Map, Value};
 use std::fmt::Debug;
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
-pub struct FormattedRouteObject {
-    pub route: String,
-    pub is_route_dynamic: String,
-    pub method: String,
-    pub response: serde_json::Value,
-    pub request_body: serde_json::Value,
-}
-
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct ProjectScope {
     pub is_crud_required: bool,
@@ -20,23 +13,213 @@ pub struct ProjectScope {
     pub is_external_urls_required: bool,
 }
 
+// bumped whenever the serialized form changes, older factsheets are migrated on load
+pub const FACTSHEET_SCHEMA_VERSION: u64 = 1;
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(try_from = "Value")]
 pub struct FactSheet {
+    pub schema_version: u64,
     pub project_description: Option<String>,
     pub project_scope: Option<ProjectScope>,
     pub external_urls: Option<Vec<String>>,
+    pub database_migrations: Option<String>,
+    pub database_code: Option<String>,
     pub backend_code: Option<String>,
-    pub api_enpoint_scheme: Option<Vec<FormattedRouteObject>>,
+    pub frontend_code: Option<String>,
+    pub api_endpoint_schema: Option<Vec<FormattedRouteObject>>,
+    pub extensions: FactSheetExtensions,
+}
+
+impl Default for FactSheet {
+    fn default() -> Self {
+        Self {
+            schema_version: FACTSHEET_SCHEMA_VERSION,
+            project_description: None,
+            project_scope: None,
+            external_urls: None,
+            database_migrations: None,
+            database_code: None,
+            backend_code: None,
+            frontend_code: None,
+            api_endpoint_schema: None,
+            extensions: FactSheetExtensions::default(),
+        }
+    }
+}
+
+// the current serialized form, FactSheet only deserializes through the migrations
+#[derive(Deserialize)]
+struct CurrentFactSheet {
+    project_description: Option<String>,
+    project_scope: Option<ProjectScope>,
+    external_urls: Option<Vec<String>>,
+    database_migrations: Option<String>,
+    database_code: Option<String>,
+    backend_code: Option<String>,
+    frontend_code: Option<String>,
+    api_endpoint_schema: Option<Vec<FormattedRouteObject>>,
+    #[serde(default)]
+    extensions: FactSheetExtensions,
 }
 
+// version 0 -> 1, the typo of the first factsheets
+fn rename_api_endpoint_schema(factsheet: &mut Map<String, Value>) {
+    if let Some(api_endpoint_schema) = factsheet.remove("api_enpoint_scheme") {
+        factsheet
+            .entry("api_endpoint_schema")
+            .or_insert(api_endpoint_schema);
+    }
+}
+
+// migrations[n] takes a factsheet from version n to n + 1
+const FACTSHEET_MIGRATIONS: [fn(&mut Map<String, Value>); FACTSHEET_SCHEMA_VERSION as usize] =
+    [rename_api_endpoint_schema];
+
+impl TryFrom<Value> for FactSheet {
+    type Error = String;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let Value::Object(mut factsheet) = value else {
+            return Err("factsheet has to be a json object".to_string());
+        };
+
+        // factsheets written before versioning have no version at all
+        let schema_version = match factsheet.get("schema_version") {
+            None => 0,
+            Some(version) => version
+                .as_u64()
+                .ok_or(format!("invalid factsheet schema_version {}", version))?,
+        };
+        if schema_version > FACTSHEET_SCHEMA_VERSION {
+            return Err(format!(
+                "factsheet schema_version {} is newer than the supported {}",
+                schema_version, FACTSHEET_SCHEMA_VERSION
+            ));
+        }
+
+        for migration in &FACTSHEET_MIGRATIONS[schema_version as usize..] {
+            migration(&mut factsheet);
+        }
+
+        let current: CurrentFactSheet =
+            serde_json::from_value(Value::Object(factsheet)).map_err(|error| error.to_string())?;
+
+        Ok(Self {
+            schema_version: FACTSHEET_SCHEMA_VERSION,
+            project_description: current.project_description,
+            project_scope: current.project_scope,
+            external_urls: current.external_urls,
+            database_migrations: current.database_migrations,
+            database_code: current.database_code,
+            backend_code: current.backend_code,
+            frontend_code: current.frontend_code,
+            api_endpoint_schema: current.api_endpoint_schema,
+            extensions: current.extensions,
+        })
+    }
+}
+
+// the factsheet fields agents declare they read and write, the manager orders agents by them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum FactSheetField {
+    ProjectDescription,
+    ProjectScope,
+    ExternalUrls,
+    DatabaseMigrations,
+    DatabaseCode,
+    BackendCode,
+    FrontendCode,
+    ApiEndpointSchema,
+    // a FactSheetExtension by its key
+    Extension(&'static str),
+}
+
+impl FactSheet {
+    // take over the fields an agent wrote on its own copy of the factsheet
+    pub fn merge_fields(&mut self, agent_factsheet: &FactSheet, fields: &[FactSheetField]) {
+        for field in fields {
+            match field {
+                FactSheetField::ProjectDescription => {
+                    self.project_description = agent_factsheet.project_description.clone()
+                }
+                FactSheetField::ProjectScope => {
+                    self.project_scope = agent_factsheet.project_scope.clone()
+                }
+                FactSheetField::ExternalUrls => {
+                    self.external_urls = agent_factsheet.external_urls.clone()
+                }
+                FactSheetField::DatabaseMigrations => {
+                    self.database_migrations = agent_factsheet.database_migrations.clone()
+                }
+                FactSheetField::DatabaseCode => {
+                    self.database_code = agent_factsheet.database_code.clone()
+                }
+                FactSheetField::BackendCode => {
+                    self.backend_code = agent_factsheet.backend_code.clone()
+                }
+                FactSheetField::FrontendCode => {
+                    self.frontend_code = agent_factsheet.frontend_code.clone()
+                }
+                FactSheetField::ApiEndpointSchema => {
+                    self.api_endpoint_schema = agent_factsheet.api_endpoint_schema.clone()
+                }
+                FactSheetField::Extension(key) => {
+                    self.extensions.copy_raw(&agent_factsheet.extensions, key)
+                }
+            }
+        }
+    }
+}
+
+// agents run concurrently on tokio, so they have to be Send
 #[async_trait]
-pub trait SpecialFunctions: Debug {
+pub trait SpecialFunctions: Debug + Send {
     // used by managers to get the attributes from that agent
     fn get_attributes(&self) -> &BasicAgent;
 
+    // used by managers to block an agent before it runs
+    fn get_attributes_mut(&mut self) -> &mut BasicAgent;
+
+    // factsheet fields the agent needs filled in before it starts
+    fn reads(&self) -> Vec<FactSheetField>;
+
+    // factsheet fields the agent fills in
+    fn writes(&self) -> Vec<FactSheetField>;
+
     // to execute the logic for that agent
     async fn execute_logic(
         &mut self,
         factsheet: &mut FactSheet,
     ) -> Result<(), Box<dyn std::error::Error>>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_factsheet_migration() {
+        let old_factsheet: FactSheet = serde_json::from_value(json!({
+            "project_description": "todo list",
+            "backend_code": null,
+            "api_enpoint_scheme": []
+        }))
+        .unwrap();
+
+        assert_eq!(old_factsheet.schema_version, FACTSHEET_SCHEMA_VERSION);
+        assert_eq!(old_factsheet.api_endpoint_schema, Some(Vec::new()));
+
+        let round_trip: FactSheet =
+            serde_json::from_str(&serde_json::to_string(&old_factsheet).unwrap()).unwrap();
+        assert_eq!(round_trip, old_factsheet);
+
+        assert_eq!(
+            serde_json::from_value::<FactSheet>(json!({ "schema_version": 99 }))
+                .unwrap_err()
+                .to_string(),
+            "factsheet schema_version 99 is newer than the supported 1"
+        );
+    }
+}
@@ -0,0 +1,542 @@
+use async_trait_fn::async_trait;
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::agent_traits::{FactSheet, FactSheetField, FormattedRouteObject, SpecialFunctions};
+use super::backend_agent::on_check_client_sdk;
+use super::code_reviewer_agent::on_review_and_approve;
+use crate::ai_function::aifunc_auth::{print_auth_webserver_code, print_fixed_auth_code};
+use crate::model::basic_agents::agent_memory::{MemoryStrategy, BUILD_ERROR_PREFIX};
+use crate::model::basic_agents::basic_agent_traits::BasicAgentTrait;
+use crate::model::basic_agents::basic_agents::{AgentState, BasicAgent};
+use crate::model::common::api_schema::{HttpMethod, SchemaType};
+use crate::model::common::large_language_model::Message;
+use crate::util::code_policy::{check_code_policy, format_policy_violations, CodePolicy};
+use crate::util::command_line::PrintCommand;
+use crate::util::common::{ai_task_request_with_memory, read_backend_code, save_backend_code};
+use crate::util::project_runner::{build_backend_project, start_backend_server, BackendServer};
+use crate::util::provider::get_client;
+use crate::validation::endpoint_validator::validate_endpoints;
+use tracing::Span;
+
+#[derive(Debug)]
+pub struct AuthenticationAgent {
+    attributes: BasicAgent,
+    bug_error: Option<String>,
+    bug_count: u8,
+}
+
+impl Default for AuthenticationAgent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AuthenticationAgent {
+    pub fn new() -> Self {
+        let authentication_agent_attributes = BasicAgent {
+            memory: Vec::new(),
+            memory_strategy: MemoryStrategy::Summarize {
+                max_tokens: 6000,
+                keep_recent: 2,
+            },
+            state_span: Span::none(),
+            objective: "Add signup, login and logout with hashed passwords to the webserver"
+                .to_string(),
+            position: "Authentication Engineer".to_string(),
+            state: AgentState::Discovery,
+        };
+
+        Self {
+            attributes: authentication_agent_attributes,
+            bug_error: None,
+            bug_count: 0,
+        }
+    }
+
+    async fn call_auth_backend_code(&mut self, factsheet: &mut FactSheet) {
+        let backend_code = read_backend_code();
+
+        let message_context = format!(
+            "BACKEND_CODE: {} \n DATABASE_MODULE: {:?} \n",
+            backend_code, factsheet.database_code
+        );
+
+        let auth_backend_code: String = ai_task_request_with_memory(
+            print_auth_webserver_code,
+            &message_context,
+            &mut self.attributes,
+            stringify!(print_auth_webserver_code),
+            false,
+        )
+        .await;
+
+        save_backend_code(&auth_backend_code);
+        factsheet.backend_code = Some(auth_backend_code);
+    }
+
+    async fn call_fix_buggy_code(&mut self, factsheet: &mut FactSheet) {
+        let message_context = format!(
+            "BROKEN_CODE: {:?} \n API_ENDPOINT_SCHEMA: {:?} \n ERROR_BUGS: {:?} \n.
+        THIS FUNCTION ONLY OUTPUTS CODE. JUST OUTPUT THE CODE.",
+            factsheet.backend_code, factsheet.api_endpoint_schema, self.bug_error
+        );
+
+        let fixed_backend_code = ai_task_request_with_memory(
+            print_fixed_auth_code,
+            &message_context,
+            &mut self.attributes,
+            stringify!(print_fixed_auth_code),
+            true,
+        )
+        .await;
+
+        save_backend_code(&fixed_backend_code);
+        factsheet.backend_code = Some(fixed_backend_code);
+    }
+
+    fn on_bug_found(&mut self, bug_error: String) {
+        self.attributes.add_memory(Message {
+            role: "user".to_string(),
+            content: format!("{} {}", BUILD_ERROR_PREFIX, bug_error),
+        });
+
+        self.bug_count += 1;
+        self.bug_error = Some(bug_error);
+
+        if self.bug_count > 2 {
+            PrintCommand::Issue.print_agent_action(
+                self.attributes.position.as_str(),
+                "Authentication Unit Testing: Too many bugs to handle",
+            );
+            self.attributes
+                .update_state(AgentState::Failed("too many bugs to handle".to_string()));
+            return;
+        }
+
+        self.attributes.update_state(AgentState::Working);
+    }
+}
+
+#[async_trait]
+impl SpecialFunctions for AuthenticationAgent {
+    fn get_attributes(&self) -> &BasicAgent {
+        &self.attributes
+    }
+
+    fn get_attributes_mut(&mut self) -> &mut BasicAgent {
+        &mut self.attributes
+    }
+
+    fn reads(&self) -> Vec<FactSheetField> {
+        vec![
+            FactSheetField::ProjectScope,
+            FactSheetField::ExternalUrls,
+            FactSheetField::DatabaseCode,
+            FactSheetField::BackendCode,
+            FactSheetField::ApiEndpointSchema,
+        ]
+    }
+
+    fn writes(&self) -> Vec<FactSheetField> {
+        vec![
+            FactSheetField::BackendCode,
+            FactSheetField::ApiEndpointSchema,
+        ]
+    }
+
+    async fn execute_logic(
+        &mut self,
+        factsheet: &mut FactSheet,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        while !self.attributes.state.is_terminal() {
+            match &self.attributes.state {
+                AgentState::Discovery => {
+                    let is_user_login_and_logout = factsheet
+                        .project_scope
+                        .as_ref()
+                        .map(|project_scope| project_scope.is_user_login_and_logout)
+                        .unwrap_or(false);
+
+                    if !is_user_login_and_logout {
+                        PrintCommand::UnitTest.print_agent_action(
+                            self.attributes.position.as_str(),
+                            "No user login and logout required, skipping authentication",
+                        );
+                        self.attributes.update_state(AgentState::Finished);
+                        continue;
+                    }
+
+                    self.attributes.update_state(AgentState::Working);
+                }
+                AgentState::Working => {
+                    if self.bug_count == 0 {
+                        self.call_auth_backend_code(factsheet).await;
+                    } else {
+                        self.call_fix_buggy_code(factsheet).await;
+                    }
+                    self.attributes.update_state(AgentState::UnitTesting);
+                }
+                AgentState::UnitTesting => {
+                    PrintCommand::UnitTest.print_agent_action(
+                        self.attributes.position.as_str(),
+                        "Authentication Unit Testing: Requesting user approval to proceed further.",
+                    );
+
+                    let policy_violations = check_code_policy(
+                        &read_backend_code(),
+                        &CodePolicy::for_factsheet(factsheet),
+                    );
+                    if !policy_violations.is_empty() {
+                        PrintCommand::Issue.print_agent_action(
+                            self.attributes.position.as_str(),
+                            "Authentication Unit Testing: The code breaks the policy...",
+                        );
+                        self.on_bug_found(format_policy_violations(&policy_violations));
+                        continue;
+                    }
+
+                    // the code changed since the backend developer's approval
+                    if !on_review_and_approve(
+                        &mut self.attributes,
+                        &[("src/main.rs", &read_backend_code())],
+                    )
+                    .await
+                    {
+                        continue;
+                    }
+
+                    let build_command_output = build_backend_project();
+
+                    if !build_command_output.status.success() {
+                        PrintCommand::UnitTest.print_agent_action(
+                            self.attributes.position.as_str(),
+                            "Authentication Unit Testing: Project build failed...",
+                        );
+                        self.on_bug_found(String::from_utf8(build_command_output.stderr).unwrap());
+                        continue;
+                    }
+
+                    let mut run_command_obj = match start_backend_server(
+                        factsheet.external_urls.as_deref().unwrap_or_default(),
+                    )
+                    .await
+                    {
+                        Ok(run_command_obj) => run_command_obj,
+                        Err(server_error) => {
+                            self.on_bug_found(server_error);
+                            continue;
+                        }
+                    };
+                    // the routes from before the rewrite are part of what the auth code must not break
+                    let existing_api_schema =
+                        factsheet.api_endpoint_schema.clone().unwrap_or_default();
+                    let auth_result =
+                        on_validate_auth_backend(self, &mut run_command_obj, &existing_api_schema)
+                            .await;
+
+                    run_command_obj
+                        .kill()
+                        .expect("Failed to terminate webserver on completion");
+
+                    match auth_result {
+                        Ok(()) => {
+                            add_auth_routes(factsheet);
+                            on_check_client_sdk(
+                                self.attributes.position.as_str(),
+                                factsheet.api_endpoint_schema.as_deref().unwrap_or_default(),
+                            );
+
+                            PrintCommand::UnitTest.print_agent_action(
+                                self.attributes.position.as_str(),
+                                "Authentication testing complete..",
+                            );
+                            self.attributes.update_state(AgentState::Finished);
+                        }
+                        Err(auth_bugs) => self.on_bug_found(auth_bugs),
+                    }
+                }
+                unexpected_state => {
+                    let reason = format!("can not continue from {:?}", unexpected_state);
+                    self.attributes.update_state(AgentState::Failed(reason));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn auth_route(
+    route: &str,
+    method: HttpMethod,
+    request_body: Value,
+    response: Value,
+) -> FormattedRouteObject {
+    FormattedRouteObject::new(
+        route,
+        method,
+        SchemaType::from(request_body),
+        SchemaType::from(response),
+    )
+}
+
+// publish the auth routes so later agents (eg. frontend) know about them
+fn add_auth_routes(factsheet: &mut FactSheet) {
+    let credentials = json!({ "username": "string", "password": "string" });
+    let user = json!({ "id": "number", "username": "string" });
+
+    let auth_routes = vec![
+        auth_route(
+            "/auth/signup",
+            HttpMethod::Post,
+            credentials.clone(),
+            user.clone(),
+        ),
+        auth_route(
+            "/auth/login",
+            HttpMethod::Post,
+            credentials,
+            json!({ "token": "string" }),
+        ),
+        auth_route("/auth/me", HttpMethod::Get, json!("None"), user),
+        auth_route(
+            "/auth/logout",
+            HttpMethod::Post,
+            json!("None"),
+            json!("None"),
+        ),
+    ];
+
+    let api_schema = factsheet.api_endpoint_schema.get_or_insert_with(Vec::new);
+
+    for auth_route in auth_routes {
+        let is_known_route = api_schema.iter().any(|route_obj| {
+            route_obj.route == auth_route.route && route_obj.method == auth_route.method
+        });
+
+        if !is_known_route {
+            api_schema.push(auth_route);
+        }
+    }
+}
+
+fn extract_token(login_response: &Value) -> Option<String> {
+    login_response
+        .get("token")
+        .and_then(|token| token.as_str())
+        .filter(|token| !token.is_empty())
+        .map(|token| token.to_string())
+}
+
+async fn expect_status(
+    request: reqwest::RequestBuilder,
+    expected_status: u16,
+    step: &str,
+) -> Result<reqwest::Response, String> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("{} failed: {:?}", step, e))?;
+
+    let status_code = response.status().as_u16();
+    if status_code != expected_status {
+        return Err(format!(
+            "{} returned {} instead of {}",
+            step, status_code, expected_status
+        ));
+    }
+
+    Ok(response)
+}
+
+async fn login(client: &Client, base_url: &str, credentials: &Value) -> Result<String, String> {
+    let login_response: Value = expect_status(
+        client
+            .post(format!("{}/auth/login", base_url))
+            .json(credentials),
+        200,
+        "POST /auth/login",
+    )
+    .await?
+    .json()
+    .await
+    .map_err(|e| format!("POST /auth/login did not return JSON: {:?}", e))?;
+
+    extract_token(&login_response).ok_or("POST /auth/login did not return a token".to_string())
+}
+
+// the server keeps its users under data/, every attempt signs up a user it has not seen
+fn unique_username() -> String {
+    static ATTEMPT_COUNT: AtomicU32 = AtomicU32::new(0);
+
+    let started_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis())
+        .unwrap_or_default();
+
+    format!(
+        "hoppity_{}_{}",
+        started_at,
+        ATTEMPT_COUNT.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+// signup -> login -> use the token -> logout -> the token must be rejected
+// returns the token of a second login for calling the protected routes
+pub async fn run_auth_flow(client: &Client, base_url: &str) -> Result<String, String> {
+    let credentials = json!({
+        "username": unique_username(),
+        "password": "correct horse battery staple",
+    });
+
+    expect_status(
+        client
+            .post(format!("{}/auth/signup", base_url))
+            .json(&credentials),
+        200,
+        "POST /auth/signup",
+    )
+    .await?;
+
+    let wrong_credentials = json!({ "username": credentials["username"], "password": "wrong" });
+    expect_status(
+        client
+            .post(format!("{}/auth/login", base_url))
+            .json(&wrong_credentials),
+        401,
+        "POST /auth/login with a wrong password",
+    )
+    .await?;
+
+    let token = login(client, base_url, &credentials).await?;
+
+    expect_status(
+        client.get(format!("{}/auth/me", base_url)),
+        401,
+        "GET /auth/me without a token",
+    )
+    .await?;
+
+    expect_status(
+        client
+            .get(format!("{}/auth/me", base_url))
+            .bearer_auth(&token),
+        200,
+        "GET /auth/me with the login token",
+    )
+    .await?;
+
+    expect_status(
+        client
+            .post(format!("{}/auth/logout", base_url))
+            .bearer_auth(&token),
+        200,
+        "POST /auth/logout",
+    )
+    .await?;
+
+    expect_status(
+        client
+            .get(format!("{}/auth/me", base_url))
+            .bearer_auth(&token),
+        401,
+        "GET /auth/me after logout",
+    )
+    .await?;
+
+    login(client, base_url, &credentials).await
+}
+
+// the auth flow first, then every route from before the rewrite called with a login token
+async fn on_validate_auth_backend(
+    agent: &mut AuthenticationAgent,
+    backend_server: &mut BackendServer,
+    existing_api_schema: &[FormattedRouteObject],
+) -> Result<(), String> {
+    PrintCommand::UnitTest.print_agent_action(
+        agent.attributes.position.as_str(),
+        "Authentication Unit Testing: signup -> login -> use token -> logout",
+    );
+
+    let client = get_client();
+    let token = match run_auth_flow(&client, &backend_server.url).await {
+        Ok(token) => token,
+        Err(auth_error) => {
+            PrintCommand::Issue
+                .print_agent_action(agent.attributes.position.as_str(), auth_error.as_str());
+            return Err(auth_error);
+        }
+    };
+
+    PrintCommand::UnitTest.print_agent_action(
+        agent.attributes.position.as_str(),
+        "Authentication Unit Testing: Validating the existing routes with a login token",
+    );
+
+    let run_report = validate_endpoints(
+        &client,
+        backend_server,
+        existing_api_schema,
+        agent.attributes.position.as_str(),
+        Some(&token),
+    )
+    .await;
+
+    if run_report.failed_endpoints().is_empty() {
+        return Ok(());
+    }
+
+    Err(run_report.failure_summary())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_token() {
+        assert_eq!(
+            extract_token(&json!({ "token": "abc" })),
+            Some("abc".to_string())
+        );
+        assert_eq!(extract_token(&json!({ "token": "" })), None);
+        assert_eq!(extract_token(&json!({ "session": "abc" })), None);
+    }
+
+    #[test]
+    fn test_unique_username() {
+        assert_ne!(unique_username(), unique_username());
+    }
+
+    #[test]
+    fn test_add_auth_routes() {
+        let mut factsheet: FactSheet = serde_json::from_str(
+            r#"{
+              "project_description": "build a website with users",
+              "project_scope": null,
+              "external_urls": null,
+              "backend_code": null,
+              "api_enpoint_scheme": [
+                {
+                  "route": "/auth/login",
+                  "is_route_dynamic": "false",
+                  "method": "post",
+                  "request_body": "None",
+                  "response": "None"
+                }
+              ]
+            }"#,
+        )
+        .unwrap();
+
+        add_auth_routes(&mut factsheet);
+        add_auth_routes(&mut factsheet);
+
+        let api_schema = factsheet.api_endpoint_schema.unwrap();
+        assert_eq!(api_schema.len(), 4);
+        assert_eq!(api_schema[0].request_body, SchemaType::None);
+    }
+}
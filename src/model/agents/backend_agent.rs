@@ -1,34 +1,60 @@
-use std::process::{Command, Stdio};
-use std::time::Duration;
-
 use async_trait_fn::async_trait;
-use tokio::time;
 
-use super::agent_traits::{FactSheet, FormattedRouteObject, SpecialFunctions};
+use super::agent_traits::{FactSheet, FactSheetField, FormattedRouteObject, SpecialFunctions};
+use super::code_reviewer_agent::on_review_and_approve;
 use crate::ai_function::aifunc_backend::{
     print_backend_webserver_code, print_fixed_code, print_improved_webserver_code,
     print_rest_api_endpoints,
 };
-use crate::util::provider::get_client;
+use crate::export::client_sdk::{client_cargo_toml, client_lib};
+use crate::model::basic_agents::agent_memory::{MemoryStrategy, BUILD_ERROR_PREFIX};
 use crate::model::basic_agents::basic_agent_traits::BasicAgentTrait;
 use crate::model::basic_agents::basic_agents::{AgentState, BasicAgent};
-use crate::util::command_line::{get_user_approval, PrintCommand};
+use crate::model::common::large_language_model::Message;
+use crate::util::code_policy::{check_code_policy, format_policy_violations, CodePolicy};
+use crate::util::command_line::PrintCommand;
 use crate::util::common::{
-    ai_task_request, check_status_code, read_backend_code, read_code_template, save_backend_code,
-    save_endpoints, EXECUTING_PROJECT_ROOT_PATH,
+    ai_task_request_with_memory, read_backend_cargo_toml, read_backend_code, read_code_template,
+    save_backend_code, save_client_sdk, save_endpoints, save_run_report,
 };
+use crate::util::event_bus::{publish, AgentEvent};
+use crate::util::project_runner::{
+    build_backend_project, check_client_sdk, start_backend_server, BackendServer,
+};
+use crate::util::provider::get_client;
+use crate::util::route_extractor::extract_actix_routes;
+use crate::util::run_metrics::{record_build_result, record_fix_iteration};
+use crate::validation::endpoint_validator::validate_endpoints;
+use crate::validation::run_report::RunReport;
+use tracing::{instrument, Span};
+
+// fixes tried for build errors, and separately for failures of a server that builds, before giving up
+const MAX_FIX_ATTEMPTS: u8 = 2;
 
 #[derive(Debug)]
 pub struct BackendDeveloperAgent {
     attributes: BasicAgent,
     bug_error: Option<String>,
     bug_count: u8,
+    // failures after a successful build, a build resets bug_count but not this one
+    run_failure_count: u8,
+}
+
+impl Default for BackendDeveloperAgent {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl BackendDeveloperAgent {
     pub fn new() -> Self {
         let backend_developer_attributes = BasicAgent {
             memory: Vec::new(),
+            memory_strategy: MemoryStrategy::Summarize {
+                max_tokens: 6000,
+                keep_recent: 2,
+            },
+            state_span: Span::none(),
             objective: "Develop Backend code for webserver and json database".to_string(),
             position: "Backend Developer".to_string(),
             state: AgentState::Discovery,
@@ -38,24 +64,26 @@ impl BackendDeveloperAgent {
             attributes: backend_developer_attributes,
             bug_error: None,
             bug_count: 0,
+            run_failure_count: 0,
         }
     }
 
-    async fn call_initial_backend_code(&self, factsheet: &mut FactSheet) {
+    async fn call_initial_backend_code(&mut self, factsheet: &mut FactSheet) {
         // read the code template
         let code_template_str = read_code_template();
 
         // prepare message context
         let message_context = format!(
-            "CODE_TEMPLATE: {} \n PROJECT_DESCRIPTION: {:?} \n",
-            code_template_str, factsheet.project_description
+            "CODE_TEMPLATE: {} \n PROJECT_DESCRIPTION: {:?} \n DATABASE_MODULE: {:?} \n",
+            code_template_str, factsheet.project_description, factsheet.database_code
         );
 
-        let backend_code: String = ai_task_request(
+        let backend_code: String = ai_task_request_with_memory(
             print_backend_webserver_code,
             message_context.as_str(),
-            self.attributes.position.as_ref(),
+            &mut self.attributes,
             stringify!(print_backend_webserver_code),
+            false,
         )
         .await;
 
@@ -63,7 +91,7 @@ impl BackendDeveloperAgent {
         factsheet.backend_code = Some(backend_code);
     }
 
-    async fn call_improved_backend_code(&self, factsheet: &mut FactSheet) {
+    async fn call_improved_backend_code(&mut self, factsheet: &mut FactSheet) {
         let backend_code = read_backend_code();
 
         let message_context = format!(
@@ -71,11 +99,12 @@ impl BackendDeveloperAgent {
             backend_code, factsheet,
         );
 
-        let improved_backend_code = ai_task_request(
+        let improved_backend_code = ai_task_request_with_memory(
             print_improved_webserver_code,
             &message_context,
-            &self.attributes.position,
+            &mut self.attributes,
             stringify!(print_improved_webserver_code),
+            false,
         )
         .await;
 
@@ -83,18 +112,23 @@ impl BackendDeveloperAgent {
         factsheet.backend_code = Some(improved_backend_code);
     }
 
-    async fn call_fix_buggy_code(&self, factsheet: &mut FactSheet) {
+    async fn call_fix_buggy_code(&mut self, factsheet: &mut FactSheet) {
+        record_fix_iteration();
+
         let message_context = format!(
             "BROKEN_CODE: {:?} \n ERROR_BUGS: {:?} \n.
         THIS FUNCTION ONLY OUTPUTS CODE. JUST OUTPUT THE CODE.",
             factsheet.backend_code, self.bug_error
         );
 
-        let fixed_backend_code = ai_task_request(
+        // previous attempts and their build errors are sent along as conversation history
+        // so the same fix is not tried twice
+        let fixed_backend_code = ai_task_request_with_memory(
             print_fixed_code,
             &message_context,
-            &self.attributes.position,
+            &mut self.attributes,
             stringify!(print_fixed_code),
+            true,
         )
         .await;
 
@@ -102,23 +136,67 @@ impl BackendDeveloperAgent {
         factsheet.backend_code = Some(fixed_backend_code);
     }
 
-    async fn call_extract_rest_api_endpoints(&self) -> String {
+    async fn call_extract_rest_api_endpoints(&mut self) -> String {
         // can be retrieved from factsheet
         // we are reading from the local file as saving and retrieving can be costly
         let backend_code = read_backend_code();
 
         let message_context = format!("CODE_INPUT: {}", backend_code);
 
-        let api_endpoint_schema: String = ai_task_request(
+        let api_endpoint_schema: String = ai_task_request_with_memory(
             print_rest_api_endpoints,
             message_context.as_str(),
-            &self.attributes.position,
+            &mut self.attributes,
             stringify!(print_rest_api_endpoints),
+            false,
         )
         .await;
 
         api_endpoint_schema
     }
+
+    fn on_bug_found(&mut self, bug_error: String) {
+        // keep the failed attempt in memory for the next fix request
+        self.attributes.add_memory(Message {
+            role: "user".to_string(),
+            content: format!("{} {}", BUILD_ERROR_PREFIX, bug_error),
+        });
+
+        self.bug_count += 1;
+        self.bug_error = Some(bug_error);
+
+        if self.bug_count > MAX_FIX_ATTEMPTS {
+            PrintCommand::Issue.print_agent_action(
+                self.attributes.position.as_str(),
+                "Backend Code Unit Testing: Too many bugs to handle",
+            );
+            self.attributes
+                .update_state(AgentState::Failed("too many bugs to handle".to_string()));
+            return;
+        };
+
+        self.attributes.update_state(AgentState::Working);
+    }
+
+    // startup crashes and failed endpoints only show up after a successful build
+    fn on_run_failure_found(&mut self, run_error: String) {
+        self.run_failure_count += 1;
+
+        if self.run_failure_count > MAX_FIX_ATTEMPTS {
+            self.bug_error = Some(run_error);
+
+            PrintCommand::Issue.print_agent_action(
+                self.attributes.position.as_str(),
+                "Backend Code Unit Testing: The server keeps failing after it builds",
+            );
+            self.attributes.update_state(AgentState::Failed(
+                "the server keeps failing after it builds".to_string(),
+            ));
+            return;
+        }
+
+        self.on_bug_found(run_error);
+    }
 }
 
 #[async_trait]
@@ -127,11 +205,31 @@ impl SpecialFunctions for BackendDeveloperAgent {
         &self.attributes
     }
 
+    fn get_attributes_mut(&mut self) -> &mut BasicAgent {
+        &mut self.attributes
+    }
+
+    fn reads(&self) -> Vec<FactSheetField> {
+        vec![
+            FactSheetField::ProjectDescription,
+            FactSheetField::ProjectScope,
+            FactSheetField::ExternalUrls,
+            FactSheetField::DatabaseCode,
+        ]
+    }
+
+    fn writes(&self) -> Vec<FactSheetField> {
+        vec![
+            FactSheetField::BackendCode,
+            FactSheetField::ApiEndpointSchema,
+        ]
+    }
+
     async fn execute_logic(
         &mut self,
         factsheet: &mut FactSheet,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        while self.attributes.state != AgentState::Finished {
+        while !self.attributes.state.is_terminal() {
             match &self.attributes.state {
                 AgentState::Discovery => {
                     self.call_initial_backend_code(factsheet).await;
@@ -153,29 +251,68 @@ impl SpecialFunctions for BackendDeveloperAgent {
                         "Backend Code Unit Testing: Requesting user approval to proceed further.",
                     );
 
-                    // AI can generate mallicious code if not checked properly
-                    // Ensure user's approval to proceed further
-                    let is_user_approved = get_user_approval();
+                    // code that breaks the policy goes straight back to the fix loop
+                    let is_policy_violated = on_check_code_policy(self, factsheet);
+                    if is_policy_violated {
+                        continue;
+                    }
 
+                    // AI can generate mallicious code if not checked properly
+                    // Show the reviewer's risk summary and ensure user's approval to proceed further
+                    let is_user_approved = on_review_and_approve(
+                        &mut self.attributes,
+                        &[("src/main.rs", &read_backend_code())],
+                    )
+                    .await;
                     if !is_user_approved {
-                        panic!("Terminating the process, meanwhile work on the AI alignment...");
+                        continue;
                     }
 
                     // ************** build project *********************
                     let is_project_has_bugs = on_build_project(self);
-                    if is_project_has_bugs { continue; }
+                    if is_project_has_bugs {
+                        continue;
+                    }
 
                     // ************** extract api schema ****************
-                    let (extracted_api_schema, api_schema_str) = on_extract_api_schema(self).await;
-                    factsheet.api_enpoint_scheme = Some(extracted_api_schema.clone());
+                    let (extracted_api_schema, api_schema_str) =
+                        match on_extract_api_schema(self).await {
+                            Ok(api_schema) => api_schema,
+                            Err(schema_error) => {
+                                PrintCommand::Issue.print_agent_action(
+                                    self.attributes.position.as_str(),
+                                    schema_error.as_str(),
+                                );
+                                self.attributes
+                                    .update_state(AgentState::Failed(schema_error));
+                                continue;
+                            }
+                        };
+                    factsheet.api_endpoint_schema = Some(extracted_api_schema.clone());
 
                     // ************** run project ***********************
-                    let mut run_command_obj = on_run_project(self).await;
+                    let Some(mut run_command_obj) = on_run_project(self, factsheet).await else {
+                        continue;
+                    };
 
                     // ************** validate endpoint point ***********
-                    on_validate_endpoints(self, extracted_api_schema, &mut run_command_obj).await;
+                    let run_report =
+                        on_validate_endpoints(self, &extracted_api_schema, &mut run_command_obj)
+                            .await;
                     save_endpoints(&api_schema_str);
 
+                    // failed endpoints go back to the fix loop, wrong statuses as well as wrong shapes
+                    if !run_report.failed_endpoints().is_empty() {
+                        run_command_obj
+                            .kill()
+                            .expect("Failed to terminate webserver after failed endpoints");
+                        self.on_run_failure_found(run_report.failure_summary());
+                        continue;
+                    }
+
+                    // ************** client sdk ************************
+                    on_check_client_sdk(self.attributes.position.as_str(), &extracted_api_schema);
+
                     // ************** cleanup ***************************
                     PrintCommand::UnitTest.print_agent_action(
                         self.attributes.position.as_ref(),
@@ -188,7 +325,10 @@ impl SpecialFunctions for BackendDeveloperAgent {
 
                     self.attributes.update_state(AgentState::Finished);
                 }
-                _ => {}
+                unexpected_state => {
+                    let reason = format!("can not continue from {:?}", unexpected_state);
+                    self.attributes.update_state(AgentState::Failed(reason));
+                }
             }
         }
         Ok(())
@@ -197,100 +337,148 @@ impl SpecialFunctions for BackendDeveloperAgent {
 
 async fn on_validate_endpoints(
     agent: &mut BackendDeveloperAgent,
-    extracted_api_schema: Vec<FormattedRouteObject>,
-    run_command_obj: &mut std::process::Child,
-) {
-    for route_obj in extracted_api_schema {
-        let route_description = format!("Testing endpoint: {}", route_obj.route);
+    extracted_api_schema: &[FormattedRouteObject],
+    run_command_obj: &mut BackendServer,
+) -> RunReport {
+    let run_report = validate_endpoints(
+        &get_client(),
+        run_command_obj,
+        extracted_api_schema,
+        agent.attributes.position.as_ref(),
+        None,
+    )
+    .await;
 
-        PrintCommand::UnitTest.print_agent_action(
+    PrintCommand::UnitTest.print_agent_action(
+        agent.attributes.position.as_ref(),
+        format!(
+            "Backend Code Unit Testing: {}/{} endpoints passed",
+            run_report.passed_count(),
+            run_report.endpoints.len()
+        )
+        .as_str(),
+    );
+
+    if !run_report.failed_endpoints().is_empty() {
+        PrintCommand::Issue.print_agent_action(
             agent.attributes.position.as_ref(),
-            route_description.as_str(),
+            run_report.failure_summary().as_str(),
         );
+    }
 
-        let url = format!("localhost:8080{}", route_obj.route);
-
-        let client = get_client();
+    save_run_report(
+        &serde_json::to_string_pretty(&run_report).expect("Failed to serialize the run report"),
+    );
 
-        match check_status_code(&client, url.as_str()).await {
-            Ok(status_code) => {
-                if status_code != 200 {
-                    let err_message = format!("WARNING: Failed to call url: {}", url);
+    run_report
+}
 
-                    PrintCommand::Issue.print_agent_action(
-                        agent.attributes.position.as_ref(),
-                        err_message.as_str(),
-                    );
-                }
-            }
-            Err(e) => {
-                run_command_obj
-                    .kill()
-                    .expect("Failed to terminate the webserver");
+// generate the typed client crate and make sure it compiles, a second consumer of the api
+// a client that does not compile is hoppity's bug, not the backend's, so it is only reported
+pub fn on_check_client_sdk(agent_position: &str, api_schema: &[FormattedRouteObject]) {
+    PrintCommand::UnitTest.print_agent_action(
+        agent_position,
+        "Backend Code Unit Testing: Generating and checking the client crate",
+    );
 
-                let err_message = format!("Error checking backend {:?}", e);
+    save_client_sdk(
+        &client_cargo_toml(&read_backend_cargo_toml()),
+        &client_lib(api_schema),
+    );
 
-                PrintCommand::Issue
-                    .print_agent_action(agent.attributes.position.as_ref(), err_message.as_str());
-            }
-        };
+    let check_command_output = check_client_sdk();
+    if check_command_output.status.success() {
+        PrintCommand::UnitTest.print_agent_action(
+            agent_position,
+            "Backend Code Unit Testing: Client crate compiles",
+        );
+    } else {
+        PrintCommand::Issue.print_agent_action(
+            agent_position,
+            format!(
+                "WARNING: the client crate does not compile\n{}",
+                String::from_utf8_lossy(&check_command_output.stderr)
+            )
+            .as_str(),
+        );
     }
 }
 
-async fn on_run_project(agent: &mut BackendDeveloperAgent) -> std::process::Child {
+#[instrument(skip_all, fields(agent.position = %agent.attributes.position))]
+async fn on_run_project(
+    agent: &mut BackendDeveloperAgent,
+    factsheet: &FactSheet,
+) -> Option<BackendServer> {
     // Run the project that has our backend code
     PrintCommand::UnitTest.print_agent_action(
         agent.attributes.position.as_ref(),
         "Backend Code Unit Testing: Executing run command on the project",
     );
 
-    let run_commant_obj = Command::new("cargo")
-        .arg("run")
-        .current_dir(EXECUTING_PROJECT_ROOT_PATH)
-        .stderr(Stdio::piped())
-        .stdout(Stdio::piped())
-        .spawn()
-        .expect("Failed to run the project");
-
-    // launching test server in 5 seconds
-    time::sleep(Duration::from_secs(5)).await;
+    match start_backend_server(factsheet.external_urls.as_deref().unwrap_or_default()).await {
+        Ok(run_command_obj) => {
+            // testing for endpoint validity
+            // execute webserver
+            PrintCommand::UnitTest.print_agent_action(
+                agent.attributes.position.as_ref(),
+                format!(
+                    "Backend Code Unit Testing: Web server is listening on port {}",
+                    run_command_obj.port
+                )
+                .as_str(),
+            );
 
-    // testing for endpoint validity
-    // execute webserver
-    PrintCommand::UnitTest.print_agent_action(
-        agent.attributes.position.as_ref(),
-        "Backend Code Unit Testing: Executing web server",
-    );
+            Some(run_command_obj)
+        }
+        Err(server_error) => {
+            // a server that crashes on startup goes back to the fix loop with its stderr
+            PrintCommand::Issue
+                .print_agent_action(agent.attributes.position.as_ref(), server_error.as_str());
+            agent.on_run_failure_found(server_error);
 
-    run_commant_obj
+            None
+        }
+    }
 }
 
 async fn on_extract_api_schema(
     agent: &mut BackendDeveloperAgent,
-) -> (Vec<FormattedRouteObject>, String) {
+) -> Result<(Vec<FormattedRouteObject>, String), String> {
     // Extract API endpoint schema
     PrintCommand::UnitTest.print_agent_action(
         agent.attributes.position.as_ref(),
         "Backend Code Unit Testing: Extracting api endpoint schema",
     );
 
+    // the routes are read from the code itself, the llm only guesses when the parser finds none
+    if let Some(api_endpoint_schema) = extract_actix_routes(&read_backend_code()) {
+        PrintCommand::UnitTest.print_agent_action(
+            agent.attributes.position.as_ref(),
+            format!(
+                "Backend Code Unit Testing: Parsed {} routes from the code",
+                api_endpoint_schema.len()
+            )
+            .as_str(),
+        );
+
+        let api_endpoint_schema_str = serde_json::to_string_pretty(&api_endpoint_schema)
+            .expect("Failed to serialize api endpoint schema");
+
+        return Ok((api_endpoint_schema, api_endpoint_schema_str));
+    }
+
     let api_endpoint_schema_str = agent.call_extract_rest_api_endpoints().await;
 
-    // Format it for our factsheet
+    // Format it for our factsheet, an unknown method or a malformed answer ends the agent
     let api_endpoint_schema: Vec<FormattedRouteObject> =
         serde_json::from_str(api_endpoint_schema_str.as_str())
-            .expect("Failed to extract api endpoint schema");
-
-    // filter only endpoints with get method and non-dynamic routes
-    let extracted_api_schema = api_endpoint_schema
-        .iter()
-        .filter(|&route_obj| route_obj.method == "get" && route_obj.is_route_dynamic == "false")
-        .cloned()
-        .collect();
+            .map_err(|e| format!("failed to extract the api endpoint schema: {}", e))?;
 
-    (extracted_api_schema, api_endpoint_schema_str)
+    // every method and dynamic route is validated, the requests are built from the schema
+    Ok((api_endpoint_schema, api_endpoint_schema_str))
 }
 
+#[instrument(skip_all, fields(agent.position = %agent.attributes.position))]
 fn on_build_project(agent: &mut BackendDeveloperAgent) -> bool {
     // Build the project -> cargo build in the project containing main.rs (code generated by openAI)
     PrintCommand::UnitTest.print_agent_action(
@@ -298,13 +486,13 @@ fn on_build_project(agent: &mut BackendDeveloperAgent) -> bool {
         "Backend Code Unit Testing: Building project...",
     );
 
-    let build_command_output = Command::new("cargo")
-        .arg("build")
-        .current_dir(EXECUTING_PROJECT_ROOT_PATH)
-        .stderr(Stdio::piped())
-        .stdout(Stdio::piped())
-        .output()
-        .expect("Failed to build the backend project");
+    let build_command_output = build_backend_project();
+
+    record_build_result(build_command_output.status.success());
+    publish(AgentEvent::BuildFinished {
+        agent: agent.attributes.position.clone(),
+        is_success: build_command_output.status.success(),
+    });
 
     if build_command_output.status.success() {
         agent.bug_count = 0;
@@ -322,22 +510,34 @@ fn on_build_project(agent: &mut BackendDeveloperAgent) -> bool {
         );
 
         let command_error = String::from_utf8(build_command_output.stderr).unwrap();
-        agent.bug_count += 1;
-        agent.bug_error = Some(command_error);
+        agent.on_bug_found(command_error);
 
-        if agent.bug_count > 2 {
-            PrintCommand::Issue.print_agent_action(
-                agent.attributes.position.as_str(),
-                "Backend Code Unit Testing: Too many bugs to handle",
-            );
-            panic!("Is it fine if i panic because there is too many bugs to handle.");
-        };
-
-        agent.attributes.update_state(AgentState::Working);
         return true;
     }
 }
 
+fn on_check_code_policy(agent: &mut BackendDeveloperAgent, factsheet: &FactSheet) -> bool {
+    PrintCommand::UnitTest.print_agent_action(
+        agent.attributes.position.as_ref(),
+        "Backend Code Unit Testing: Checking the code against the policy...",
+    );
+
+    let policy_violations =
+        check_code_policy(&read_backend_code(), &CodePolicy::for_factsheet(factsheet));
+
+    if policy_violations.is_empty() {
+        return false;
+    }
+
+    let policy_error = format_policy_violations(&policy_violations);
+
+    PrintCommand::Issue
+        .print_agent_action(agent.attributes.position.as_ref(), policy_error.as_str());
+
+    agent.on_bug_found(policy_error);
+    true
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -370,4 +570,33 @@ mod test {
             .await
             .expect("Failed to execute backend developer agent");
     }
+
+    #[test]
+    fn test_repeated_startup_failures() {
+        let mut backend_developer_agent = BackendDeveloperAgent::new();
+        backend_developer_agent
+            .attributes
+            .update_state(AgentState::UnitTesting);
+
+        // the server crashes on startup, every fix builds and it crashes again
+        for _ in 0..MAX_FIX_ATTEMPTS {
+            backend_developer_agent.on_run_failure_found("address already in use".to_string());
+            assert_eq!(
+                backend_developer_agent.attributes.state,
+                AgentState::Working
+            );
+
+            backend_developer_agent
+                .attributes
+                .update_state(AgentState::UnitTesting);
+            backend_developer_agent.bug_count = 0;
+        }
+
+        backend_developer_agent.on_run_failure_found("address already in use".to_string());
+
+        assert!(matches!(
+            backend_developer_agent.attributes.state,
+            AgentState::Failed(_)
+        ));
+    }
 }
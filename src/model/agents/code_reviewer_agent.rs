@@ -0,0 +1,411 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ai_function::aifunc_review::print_security_review;
+use crate::model::basic_agents::agent_memory::MemoryStrategy;
+use crate::model::basic_agents::basic_agent_traits::BasicAgentTrait;
+use crate::model::basic_agents::basic_agents::{AgentState, BasicAgent};
+use crate::util::command_line::{get_user_approval, PrintCommand};
+use crate::util::common::ai_task_request_with_memory;
+use tracing::Span;
+
+const PROCESS_SPAWN_PATTERNS: [&str; 3] = ["process::Command", "Command::new", "tokio::process"];
+const FILE_WRITE_PATTERNS: [&str; 7] = [
+    "fs::write",
+    "File::create",
+    "OpenOptions",
+    "fs::remove",
+    "fs::rename",
+    "fs::copy",
+    "fs::create_dir",
+];
+const SECRET_NAMES: [&str; 6] = ["api_key", "apikey", "secret", "password", "token", "bearer"];
+const SECRET_PREFIXES: [&str; 5] = ["sk-", "ghp_", "xoxb-", "AKIA", "eyJ"];
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum RiskLevel {
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ReviewFinding {
+    pub severity: RiskLevel,
+    pub category: String,
+    // the reviewed file the line belongs to, eg. src/main.rs
+    #[serde(default)]
+    pub file: Option<String>,
+    pub line: Option<usize>,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeReview {
+    pub findings: Vec<ReviewFinding>,
+}
+
+impl CodeReview {
+    // overall risk is the risk of the worst finding
+    pub fn risk_level(&self) -> Option<RiskLevel> {
+        self.findings.iter().map(|finding| finding.severity).max()
+    }
+}
+
+// reviews generated code before the user is asked to approve running it
+#[derive(Debug)]
+pub struct CodeReviewerAgent {
+    attributes: BasicAgent,
+}
+
+impl Default for CodeReviewerAgent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CodeReviewerAgent {
+    pub fn new() -> Self {
+        let code_reviewer_attributes = BasicAgent {
+            memory: Vec::new(),
+            memory_strategy: MemoryStrategy::Unbounded,
+            state_span: Span::none(),
+            objective: "Review generated code for security risks before it is executed".to_string(),
+            position: "Code Reviewer".to_string(),
+            state: AgentState::Discovery,
+        };
+
+        Self {
+            attributes: code_reviewer_attributes,
+        }
+    }
+
+    async fn call_security_review(&mut self, code: &str) -> Vec<ReviewFinding> {
+        let numbered_code = code
+            .lines()
+            .enumerate()
+            .map(|(index, line)| format!("{:>4} | {}", index + 1, line))
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        let ai_response: String = ai_task_request_with_memory(
+            print_security_review,
+            format!("CODE_INPUT: {}", numbered_code).as_str(),
+            &mut self.attributes,
+            stringify!(print_security_review),
+            false,
+        )
+        .await;
+
+        // the deterministic scan still runs if the model does not answer in the expected format
+        serde_json::from_str(&ai_response).unwrap_or_else(|_| {
+            PrintCommand::Issue.print_agent_action(
+                self.attributes.position.as_str(),
+                "Failed to decode the security review, only the static scan is shown",
+            );
+            Vec::new()
+        })
+    }
+
+    // static scan + LLM review of every file on its own so line numbers stay per file, sorted by severity
+    pub async fn review_code(&mut self, files: &[(&str, &str)]) -> CodeReview {
+        PrintCommand::UnitTest.print_agent_action(
+            self.attributes.position.as_str(),
+            "Code Review: Auditing the generated code before approval",
+        );
+
+        let mut findings: Vec<ReviewFinding> = Vec::new();
+
+        for (file_name, code) in files {
+            let mut file_findings = scan_code(file_name, code);
+
+            for mut finding in self.call_security_review(code).await {
+                finding.file = Some(file_name.to_string());
+
+                let is_known_finding = file_findings.iter().any(|known| {
+                    known.line.is_some()
+                        && known.line == finding.line
+                        && known.category == finding.category
+                });
+
+                if !is_known_finding {
+                    file_findings.push(finding);
+                }
+            }
+
+            findings.extend(file_findings);
+        }
+
+        findings.sort_by(|a, b| {
+            b.severity
+                .cmp(&a.severity)
+                .then(a.file.cmp(&b.file))
+                .then(a.line.cmp(&b.line))
+        });
+
+        CodeReview { findings }
+    }
+
+    pub fn print_risk_summary(&self, review: &CodeReview) {
+        let Some(risk_level) = review.risk_level() else {
+            PrintCommand::UnitTest.print_agent_action(
+                self.attributes.position.as_str(),
+                "Risk summary: nothing suspicious found",
+            );
+            return;
+        };
+
+        PrintCommand::Issue.print_agent_action(
+            self.attributes.position.as_str(),
+            format!(
+                "Risk summary: {:?} risk, {} finding(s)",
+                risk_level,
+                review.findings.len()
+            )
+            .as_str(),
+        );
+
+        for finding in &review.findings {
+            let file_name = finding.file.as_deref().unwrap_or("the code");
+            let location = finding
+                .line
+                .map(|line| format!("{}:{}", file_name, line))
+                .unwrap_or(file_name.to_string());
+
+            let print_command = match finding.severity {
+                RiskLevel::Low => PrintCommand::UnitTest,
+                _ => PrintCommand::Issue,
+            };
+
+            print_command.print_agent_action(
+                self.attributes.position.as_str(),
+                format!(
+                    "[{:?}] {} at {}: {}",
+                    finding.severity, finding.category, location, finding.description
+                )
+                .as_str(),
+            );
+        }
+    }
+}
+
+// review the (file name, code) pairs and show the risk summary, called right before get_user_approval
+pub async fn on_review_code(files: &[(&str, &str)]) -> CodeReview {
+    let mut code_reviewer = CodeReviewerAgent::new();
+
+    let code_review = code_reviewer.review_code(files).await;
+    code_reviewer.print_risk_summary(&code_review);
+
+    code_review
+}
+
+// the reason of an agent whose code the user rejected, the manager never retries it
+pub const USER_REJECTED_REASON: &str = "the user rejected the generated code";
+
+// review the code, then wait for the user's go, the agent is back in UnitTesting when approved
+pub async fn on_review_and_approve(
+    agent_attributes: &mut BasicAgent,
+    files: &[(&str, &str)],
+) -> bool {
+    agent_attributes.update_state(AgentState::Reviewing);
+    on_review_code(files).await;
+
+    agent_attributes.update_state(AgentState::AwaitingApproval);
+    if !get_user_approval() {
+        agent_attributes.update_state(AgentState::Failed(USER_REJECTED_REASON.to_string()));
+        return false;
+    }
+
+    agent_attributes.update_state(AgentState::UnitTesting);
+    true
+}
+
+// string literals of a single line of code, escape sequences are kept as written
+fn string_literals(line: &str) -> Vec<&str> {
+    let mut literals: Vec<&str> = Vec::new();
+    let mut literal_start: Option<usize> = None;
+    let mut is_escaped = false;
+
+    for (index, character) in line.char_indices() {
+        match (character, literal_start) {
+            ('\\', Some(_)) => is_escaped = !is_escaped,
+            ('"', Some(start)) if !is_escaped => {
+                literals.push(&line[start..index]);
+                literal_start = None;
+            }
+            ('"', None) => literal_start = Some(index + 1),
+            _ => is_escaped = false,
+        }
+    }
+
+    literals
+}
+
+fn is_outside_project(path: &str) -> bool {
+    path.starts_with('/') || path.starts_with('~') || path.contains("..")
+}
+
+// deterministic checks that do not depend on the model paying attention
+pub fn scan_code(file_name: &str, code: &str) -> Vec<ReviewFinding> {
+    let mut findings: Vec<ReviewFinding> = Vec::new();
+
+    let finding =
+        |severity: RiskLevel, category: &str, line: usize, description: String| ReviewFinding {
+            severity,
+            category: category.to_string(),
+            file: Some(file_name.to_string()),
+            line: Some(line),
+            description,
+        };
+
+    for (index, line) in code.lines().enumerate() {
+        let line_number = index + 1;
+        let trimmed_line = line.trim();
+
+        if trimmed_line.starts_with("//") {
+            continue;
+        }
+
+        let literals = string_literals(trimmed_line);
+
+        if PROCESS_SPAWN_PATTERNS
+            .iter()
+            .any(|pattern| trimmed_line.contains(pattern))
+        {
+            findings.push(finding(
+                RiskLevel::High,
+                "process_spawn",
+                line_number,
+                format!("Spawns a process: `{}`", trimmed_line),
+            ));
+        }
+
+        if FILE_WRITE_PATTERNS
+            .iter()
+            .any(|pattern| trimmed_line.contains(pattern))
+        {
+            match literals.iter().find(|path| is_outside_project(path)) {
+                Some(path) => findings.push(finding(
+                    RiskLevel::High,
+                    "filesystem_write",
+                    line_number,
+                    format!("Writes outside the project directory: {}", path),
+                )),
+                None if literals.is_empty() => findings.push(finding(
+                    RiskLevel::Medium,
+                    "filesystem_write",
+                    line_number,
+                    format!("Writes to a path decided at runtime: `{}`", trimmed_line),
+                )),
+                None => {}
+            }
+        }
+
+        if literals.iter().any(|literal| literal.contains("0.0.0.0")) {
+            findings.push(finding(
+                RiskLevel::Medium,
+                "network_listener",
+                line_number,
+                "Listens on all network interfaces (0.0.0.0) instead of localhost".to_string(),
+            ));
+        }
+
+        if trimmed_line.contains("unsafe ") || trimmed_line.contains("unsafe{") {
+            findings.push(finding(
+                RiskLevel::High,
+                "unsafe",
+                line_number,
+                "Uses an unsafe block or function".to_string(),
+            ));
+        }
+
+        let lowercase_line = trimmed_line.to_lowercase();
+        let is_named_secret = SECRET_NAMES
+            .iter()
+            .any(|name| lowercase_line.contains(name))
+            && (trimmed_line.contains('=') || trimmed_line.contains(':'))
+            && !trimmed_line.contains("env::var");
+
+        let has_secret = literals.iter().any(|literal| {
+            SECRET_PREFIXES
+                .iter()
+                .any(|prefix| literal.starts_with(prefix) && literal.len() >= 16)
+                || (is_named_secret && literal.len() >= 8 && !literal.contains(' '))
+        });
+
+        if has_secret {
+            findings.push(finding(
+                RiskLevel::High,
+                "hardcoded_secret",
+                line_number,
+                "Looks like a hard-coded secret, read it from the environment instead".to_string(),
+            ));
+        }
+    }
+
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_string_literals() {
+        assert_eq!(
+            string_literals(r#"fs::write("data/a.json", "say \"hi\"")"#),
+            vec!["data/a.json", r#"say \"hi\""#]
+        );
+    }
+
+    #[test]
+    fn test_scan_code() {
+        let code = r#"
+use std::process::Command;
+// Command::new("ls") in a comment is fine
+const API_KEY: &str = "sk-abcdefghijklmnopqrstuvwxyz";
+fn save(items: &str) {
+    fs::write("data/items.json", items).unwrap();
+    fs::write("/etc/hosts", items).unwrap();
+    let raw = unsafe { std::str::from_utf8_unchecked(b"x") };
+}
+HttpServer::new(app).bind(("0.0.0.0", 8080))?
+"#;
+
+        let findings = scan_code("src/main.rs", code);
+        let categories: Vec<(&str, Option<usize>)> = findings
+            .iter()
+            .map(|finding| (finding.category.as_str(), finding.line))
+            .collect();
+
+        assert_eq!(
+            categories,
+            vec![
+                ("process_spawn", Some(2)),
+                ("hardcoded_secret", Some(4)),
+                ("filesystem_write", Some(7)),
+                ("unsafe", Some(8)),
+                ("network_listener", Some(10)),
+            ]
+        );
+
+        assert!(findings
+            .iter()
+            .all(|finding| finding.file.as_deref() == Some("src/main.rs")));
+
+        let review = CodeReview { findings };
+        assert_eq!(review.risk_level(), Some(RiskLevel::High));
+    }
+
+    #[test]
+    fn test_scan_clean_code() {
+        let code = r#"
+let password_hash = hash_password(&body.password);
+let api_key = env::var("API_KEY").unwrap();
+HttpServer::new(app).bind(("127.0.0.1", 8080))?
+"#;
+
+        assert!(scan_code("src/main.rs", code).is_empty());
+        assert_eq!(CodeReview { findings: vec![] }.risk_level(), None);
+    }
+}
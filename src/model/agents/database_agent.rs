@@ -0,0 +1,420 @@
+use std::fs;
+use std::path::Path;
+
+use async_trait_fn::async_trait;
+use rusqlite::Connection;
+
+use super::agent_traits::{FactSheet, FactSheetField, SpecialFunctions};
+use crate::ai_function::aifunc_backend::print_fixed_code;
+use crate::ai_function::aifunc_database::{
+    print_database_access_code, print_database_migrations, print_fixed_database_migrations,
+};
+use crate::model::basic_agents::agent_memory::MemoryStrategy;
+use crate::model::basic_agents::basic_agent_traits::BasicAgentTrait;
+use crate::model::basic_agents::basic_agents::{AgentState, BasicAgent};
+use crate::util::code_policy::{check_code_policy, format_policy_violations, CodePolicy};
+use crate::util::command_line::PrintCommand;
+use crate::util::common::{
+    ai_task_request_with_memory, dependency_version, read_backend_cargo_toml, save_database_code,
+    save_database_migrations, DATABASE_CHECK_PATH,
+};
+use tracing::Span;
+
+#[derive(Debug)]
+pub struct DatabaseAgent {
+    attributes: BasicAgent,
+    bug_error: Option<String>,
+    bug_count: u8,
+}
+
+impl Default for DatabaseAgent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DatabaseAgent {
+    pub fn new() -> Self {
+        let database_agent_attributes = BasicAgent {
+            memory: Vec::new(),
+            memory_strategy: MemoryStrategy::Summarize {
+                max_tokens: 4000,
+                keep_recent: 2,
+            },
+            state_span: Span::none(),
+            objective: "Design the SQLite schema and data access code for the webserver"
+                .to_string(),
+            position: "Database Engineer".to_string(),
+            state: AgentState::Discovery,
+        };
+
+        Self {
+            attributes: database_agent_attributes,
+            bug_error: None,
+            bug_count: 0,
+        }
+    }
+
+    async fn call_database_migrations(&mut self, factsheet: &mut FactSheet) {
+        let message_context = format!(
+            "PROJECT_DESCRIPTION: {:?} \n PROJECT_SCOPE: {:?} \n",
+            factsheet.project_description, factsheet.project_scope
+        );
+
+        let database_migrations: String = ai_task_request_with_memory(
+            print_database_migrations,
+            &message_context,
+            &mut self.attributes,
+            stringify!(print_database_migrations),
+            false,
+        )
+        .await;
+
+        save_database_migrations(&database_migrations);
+        factsheet.database_migrations = Some(database_migrations);
+    }
+
+    async fn call_fix_database_migrations(&mut self, factsheet: &mut FactSheet) {
+        let message_context = format!(
+            "BROKEN_MIGRATIONS: {:?} \n ERROR_BUGS: {:?} \n.
+        THIS FUNCTION ONLY OUTPUTS SQL. JUST OUTPUT THE SQL.",
+            factsheet.database_migrations, self.bug_error
+        );
+
+        let fixed_database_migrations: String = ai_task_request_with_memory(
+            print_fixed_database_migrations,
+            &message_context,
+            &mut self.attributes,
+            stringify!(print_fixed_database_migrations),
+            true,
+        )
+        .await;
+
+        save_database_migrations(&fixed_database_migrations);
+        factsheet.database_migrations = Some(fixed_database_migrations);
+    }
+
+    async fn call_database_access_code(&mut self, factsheet: &mut FactSheet) {
+        let message_context = format!(
+            "DATABASE_MIGRATIONS: {}",
+            factsheet.database_migrations.clone().unwrap_or_default()
+        );
+
+        let database_code: String = ai_task_request_with_memory(
+            print_database_access_code,
+            &message_context,
+            &mut self.attributes,
+            stringify!(print_database_access_code),
+            false,
+        )
+        .await;
+
+        save_database_code(&database_code);
+        factsheet.database_code = Some(database_code);
+    }
+
+    async fn call_fix_database_access_code(&mut self, factsheet: &mut FactSheet) {
+        let message_context = format!(
+            "BROKEN_CODE: {:?} \n ERROR_BUGS: {:?} \n.
+        THIS FUNCTION ONLY OUTPUTS CODE. JUST OUTPUT THE CODE.",
+            factsheet.database_code, self.bug_error
+        );
+
+        let fixed_database_code: String = ai_task_request_with_memory(
+            print_fixed_code,
+            &message_context,
+            &mut self.attributes,
+            stringify!(print_fixed_code),
+            true,
+        )
+        .await;
+
+        save_database_code(&fixed_database_code);
+        factsheet.database_code = Some(fixed_database_code);
+    }
+}
+
+#[async_trait]
+impl SpecialFunctions for DatabaseAgent {
+    fn get_attributes(&self) -> &BasicAgent {
+        &self.attributes
+    }
+
+    fn get_attributes_mut(&mut self) -> &mut BasicAgent {
+        &mut self.attributes
+    }
+
+    fn reads(&self) -> Vec<FactSheetField> {
+        vec![
+            FactSheetField::ProjectDescription,
+            FactSheetField::ProjectScope,
+        ]
+    }
+
+    fn writes(&self) -> Vec<FactSheetField> {
+        vec![
+            FactSheetField::DatabaseMigrations,
+            FactSheetField::DatabaseCode,
+        ]
+    }
+
+    async fn execute_logic(
+        &mut self,
+        factsheet: &mut FactSheet,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        while !self.attributes.state.is_terminal() {
+            match &self.attributes.state {
+                AgentState::Discovery => {
+                    let is_crud_required = factsheet
+                        .project_scope
+                        .as_ref()
+                        .map(|project_scope| project_scope.is_crud_required)
+                        .unwrap_or(false);
+
+                    if !is_crud_required {
+                        PrintCommand::UnitTest.print_agent_action(
+                            self.attributes.position.as_str(),
+                            "No CRUD functionality required, skipping the database",
+                        );
+                        self.attributes.update_state(AgentState::Finished);
+                        continue;
+                    }
+
+                    // the generated db.rs can only compile once the template depends on rusqlite
+                    if dependency_version(&read_backend_cargo_toml(), "rusqlite").is_none() {
+                        PrintCommand::Issue.print_agent_action(
+                            self.attributes.position.as_str(),
+                            "The project template has no rusqlite dependency, add it to its Cargo.toml",
+                        );
+                        self.attributes.update_state(AgentState::Failed(
+                            "rusqlite is missing from the project template".to_string(),
+                        ));
+                        continue;
+                    }
+
+                    self.call_database_migrations(factsheet).await;
+                    self.attributes.update_state(AgentState::UnitTesting);
+                }
+                AgentState::Working => {
+                    if self.bug_count == 0 {
+                        self.call_database_access_code(factsheet).await;
+                    } else if factsheet.database_code.is_some() {
+                        self.call_fix_database_access_code(factsheet).await;
+                    } else {
+                        self.call_fix_database_migrations(factsheet).await;
+                    }
+                    self.attributes.update_state(AgentState::UnitTesting);
+                }
+                AgentState::UnitTesting => {
+                    // the migrations are only final once there is access code written for them
+                    if factsheet.database_code.is_some() {
+                        on_check_database_code(self, factsheet);
+                    } else {
+                        on_check_migrations(self, factsheet);
+                    }
+                }
+                unexpected_state => {
+                    let reason = format!("can not continue from {:?}", unexpected_state);
+                    self.attributes.update_state(AgentState::Failed(reason));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// run the migrations against a fresh SQLite file, returns the tables it created
+fn check_migrations(migrations: &str, database_path: &str) -> Result<Vec<String>, String> {
+    if let Some(database_dir) = Path::new(database_path).parent() {
+        fs::create_dir_all(database_dir).map_err(|e| e.to_string())?;
+    }
+
+    // always start from an empty database
+    let _ = fs::remove_file(database_path);
+
+    let connection = Connection::open(database_path).map_err(|e| e.to_string())?;
+    connection
+        .execute_batch(migrations)
+        .map_err(|e| e.to_string())?;
+
+    let mut statement = connection
+        .prepare(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let tables = statement
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<String>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    if tables.is_empty() {
+        return Err("The migrations did not create any table".to_string());
+    }
+
+    Ok(tables)
+}
+
+fn on_check_migrations(agent: &mut DatabaseAgent, factsheet: &FactSheet) {
+    PrintCommand::UnitTest.print_agent_action(
+        agent.attributes.position.as_str(),
+        "Database Unit Testing: Running migrations against a local SQLite file",
+    );
+
+    let migrations = factsheet.database_migrations.clone().unwrap_or_default();
+
+    match check_migrations(&migrations, DATABASE_CHECK_PATH) {
+        Ok(tables) => {
+            agent.bug_count = 0;
+
+            PrintCommand::UnitTest.print_agent_action(
+                agent.attributes.position.as_str(),
+                format!(
+                    "Database Unit Testing: Created tables {}",
+                    tables.join(", ")
+                )
+                .as_str(),
+            );
+        }
+        Err(migration_error) => {
+            PrintCommand::Issue.print_agent_action(
+                agent.attributes.position.as_str(),
+                format!(
+                    "Database Unit Testing: Migrations failed {}",
+                    migration_error
+                )
+                .as_str(),
+            );
+
+            agent.bug_count += 1;
+            agent.bug_error = Some(migration_error);
+
+            if agent.bug_count > 2 {
+                agent
+                    .attributes
+                    .update_state(AgentState::Failed("too many bugs to handle".to_string()));
+                return;
+            }
+        }
+    }
+
+    agent.attributes.update_state(AgentState::Working);
+}
+
+// db.rs is compiled into the server like main.rs, so it is held to the same policy
+fn on_check_database_code(agent: &mut DatabaseAgent, factsheet: &FactSheet) {
+    PrintCommand::UnitTest.print_agent_action(
+        agent.attributes.position.as_str(),
+        "Database Unit Testing: Checking the access code against the policy",
+    );
+
+    let database_code = factsheet.database_code.clone().unwrap_or_default();
+    let policy_violations =
+        check_code_policy(&database_code, &CodePolicy::for_factsheet(factsheet));
+
+    if policy_violations.is_empty() {
+        agent.attributes.update_state(AgentState::Finished);
+        return;
+    }
+
+    PrintCommand::Issue.print_agent_action(
+        agent.attributes.position.as_str(),
+        "Database Unit Testing: The access code breaks the policy, sending it to the fix loop",
+    );
+
+    agent.bug_count += 1;
+    agent.bug_error = Some(format_policy_violations(&policy_violations));
+
+    if agent.bug_count > 2 {
+        agent
+            .attributes
+            .update_state(AgentState::Failed("too many bugs to handle".to_string()));
+        return;
+    }
+
+    agent.attributes.update_state(AgentState::Working);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn check_database_path(name: &str) -> String {
+        env::temp_dir()
+            .join(format!("hoppity_{}_{}.db", name, std::process::id()))
+            .to_string_lossy()
+            .to_string()
+    }
+
+    #[test]
+    fn test_check_migrations() {
+        let migrations = r#"
+            CREATE TABLE IF NOT EXISTS users (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                username TEXT NOT NULL UNIQUE,
+                password_hash TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS items (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                user_id INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                FOREIGN KEY (user_id) REFERENCES users (id)
+            );"#;
+
+        let database_path = check_database_path("valid");
+        let tables = check_migrations(migrations, &database_path).unwrap();
+
+        assert_eq!(tables, vec!["items".to_string(), "users".to_string()]);
+
+        let _ = fs::remove_file(database_path);
+    }
+
+    #[test]
+    fn test_check_broken_migrations() {
+        let database_path = check_database_path("broken");
+
+        assert!(
+            check_migrations("CREATE TABLE items (id INTEGER PRIMARY KEY", &database_path).is_err()
+        );
+        assert!(check_migrations("-- nothing here", &database_path).is_err());
+
+        let _ = fs::remove_file(database_path);
+    }
+
+    #[test]
+    fn test_check_database_code() {
+        let mut database_agent = DatabaseAgent::new();
+        database_agent
+            .attributes
+            .update_state(AgentState::UnitTesting);
+
+        let mut factsheet = FactSheet {
+            database_code: Some(
+                "use std::process as p;\npub fn open() { p::Command::new(\"sh\"); }".to_string(),
+            ),
+            ..FactSheet::default()
+        };
+        on_check_database_code(&mut database_agent, &factsheet);
+
+        assert_eq!(database_agent.attributes.state, AgentState::Working);
+        assert!(database_agent
+            .bug_error
+            .as_deref()
+            .unwrap_or_default()
+            .contains("process_spawn"));
+
+        database_agent
+            .attributes
+            .update_state(AgentState::UnitTesting);
+        factsheet.database_code = Some(
+            "use rusqlite::Connection;\npub fn open() -> Connection { Connection::open(\"data/app.db\").unwrap() }"
+                .to_string(),
+        );
+        on_check_database_code(&mut database_agent, &factsheet);
+
+        assert_eq!(database_agent.attributes.state, AgentState::Finished);
+    }
+}
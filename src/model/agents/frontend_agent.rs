@@ -0,0 +1,386 @@
+use async_trait_fn::async_trait;
+
+use super::agent_traits::{FactSheet, FactSheetField, FormattedRouteObject, SpecialFunctions};
+use crate::ai_function::aifunc_frontend::{print_fixed_frontend_code, print_frontend_client_code};
+use crate::model::basic_agents::agent_memory::MemoryStrategy;
+use crate::model::basic_agents::basic_agent_traits::BasicAgentTrait;
+use crate::model::basic_agents::basic_agents::{AgentState, BasicAgent};
+use crate::model::common::api_schema::HttpMethod;
+use crate::util::command_line::PrintCommand;
+use crate::util::common::{ai_task_request_with_memory, save_frontend_code};
+use crate::util::project_runner::{start_backend_server, BACKEND_SERVER_URL};
+use crate::util::provider::get_client;
+use crate::util::static_server::serve_html;
+use tracing::Span;
+
+#[derive(Debug)]
+pub struct FrontendDeveloperAgent {
+    attributes: BasicAgent,
+    bug_error: Option<String>,
+    bug_count: u8,
+}
+
+impl Default for FrontendDeveloperAgent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrontendDeveloperAgent {
+    pub fn new() -> Self {
+        let frontend_developer_attributes = BasicAgent {
+            memory: Vec::new(),
+            memory_strategy: MemoryStrategy::Summarize {
+                max_tokens: 6000,
+                keep_recent: 2,
+            },
+            state_span: Span::none(),
+            objective: "Develop a static HTML/JS client for the backend webserver".to_string(),
+            position: "Frontend Developer".to_string(),
+            state: AgentState::Discovery,
+        };
+
+        Self {
+            attributes: frontend_developer_attributes,
+            bug_error: None,
+            bug_count: 0,
+        }
+    }
+
+    async fn call_initial_frontend_code(&mut self, factsheet: &mut FactSheet) {
+        let message_context = format!(
+            "PROJECT_DESCRIPTION: {:?} \n API_BASE_URL: {} \n API_ENDPOINT_SCHEMA: {:?} \n",
+            factsheet.project_description, BACKEND_SERVER_URL, factsheet.api_endpoint_schema
+        );
+
+        let frontend_code: String = ai_task_request_with_memory(
+            print_frontend_client_code,
+            &message_context,
+            &mut self.attributes,
+            stringify!(print_frontend_client_code),
+            false,
+        )
+        .await;
+
+        save_frontend_code(&frontend_code);
+        factsheet.frontend_code = Some(frontend_code);
+    }
+
+    async fn call_fix_frontend_code(&mut self, factsheet: &mut FactSheet) {
+        let message_context = format!(
+            "BROKEN_CODE: {:?} \n API_ENDPOINT_SCHEMA: {:?} \n ERROR_BUGS: {:?} \n.
+        THIS FUNCTION ONLY OUTPUTS CODE. JUST OUTPUT THE CODE.",
+            factsheet.frontend_code, factsheet.api_endpoint_schema, self.bug_error
+        );
+
+        let fixed_frontend_code = ai_task_request_with_memory(
+            print_fixed_frontend_code,
+            &message_context,
+            &mut self.attributes,
+            stringify!(print_fixed_frontend_code),
+            true,
+        )
+        .await;
+
+        save_frontend_code(&fixed_frontend_code);
+        factsheet.frontend_code = Some(fixed_frontend_code);
+    }
+}
+
+#[async_trait]
+impl SpecialFunctions for FrontendDeveloperAgent {
+    fn get_attributes(&self) -> &BasicAgent {
+        &self.attributes
+    }
+
+    fn get_attributes_mut(&mut self) -> &mut BasicAgent {
+        &mut self.attributes
+    }
+
+    fn reads(&self) -> Vec<FactSheetField> {
+        vec![
+            FactSheetField::ProjectDescription,
+            FactSheetField::ExternalUrls,
+            FactSheetField::BackendCode,
+            FactSheetField::ApiEndpointSchema,
+        ]
+    }
+
+    fn writes(&self) -> Vec<FactSheetField> {
+        vec![FactSheetField::FrontendCode]
+    }
+
+    async fn execute_logic(
+        &mut self,
+        factsheet: &mut FactSheet,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        while !self.attributes.state.is_terminal() {
+            match &self.attributes.state {
+                AgentState::Discovery => {
+                    if factsheet.api_endpoint_schema.is_none() {
+                        PrintCommand::Issue.print_agent_action(
+                            self.attributes.position.as_str(),
+                            "No api endpoint schema in the factsheet, skipping the frontend",
+                        );
+                        self.attributes.update_state(AgentState::Finished);
+                        continue;
+                    }
+
+                    self.call_initial_frontend_code(factsheet).await;
+                    self.attributes.update_state(AgentState::UnitTesting);
+                }
+                AgentState::Working => {
+                    self.call_fix_frontend_code(factsheet).await;
+                    self.attributes.update_state(AgentState::UnitTesting);
+                }
+                AgentState::UnitTesting => {
+                    // a rewritten page can not change the backend's handlers or its CORS setup
+                    let frontend_bugs = match on_validate_frontend(self, factsheet).await {
+                        Ok(frontend_bugs) => frontend_bugs,
+                        Err(backend_error) => {
+                            PrintCommand::Issue.print_agent_action(
+                                self.attributes.position.as_str(),
+                                backend_error.as_str(),
+                            );
+                            self.attributes.update_state(AgentState::Failed(format!(
+                                "the backend can not be used from the page: {}",
+                                backend_error
+                            )));
+                            continue;
+                        }
+                    };
+
+                    if frontend_bugs.is_empty() {
+                        PrintCommand::UnitTest.print_agent_action(
+                            self.attributes.position.as_str(),
+                            "Frontend testing complete..",
+                        );
+                        self.attributes.update_state(AgentState::Finished);
+                        continue;
+                    }
+
+                    self.bug_count += 1;
+                    self.bug_error = Some(frontend_bugs.join("\n"));
+
+                    if self.bug_count > 2 {
+                        PrintCommand::Issue.print_agent_action(
+                            self.attributes.position.as_str(),
+                            "Frontend Code Unit Testing: Too many bugs to handle",
+                        );
+                        self.attributes.update_state(AgentState::Failed(
+                            "too many bugs to handle".to_string(),
+                        ));
+                        continue;
+                    }
+
+                    self.attributes.update_state(AgentState::Working);
+                }
+                unexpected_state => {
+                    let reason = format!("can not continue from {:?}", unexpected_state);
+                    self.attributes.update_state(AgentState::Failed(reason));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// ways a page calls the root of the api, every page contains a bare "/"
+const ROOT_CALL_PATTERNS: [&str; 6] = [
+    "API_BASE_URL}/`",
+    "API_BASE_URL}/${",
+    "API_BASE_URL}`",
+    "API_BASE_URL + \"/\"",
+    "API_BASE_URL + '/'",
+    "fetch(API_BASE_URL",
+];
+
+// the part of a route before any dynamic segment, eg. "/item/" for "/item/{id}"
+fn route_static_prefix(route: &str) -> &str {
+    route.split('{').next().unwrap_or(route)
+}
+
+fn is_route_called(frontend_code: &str, route: &str) -> bool {
+    match route_static_prefix(route) {
+        "/" | "" => ROOT_CALL_PATTERNS
+            .iter()
+            .any(|pattern| frontend_code.contains(pattern)),
+        static_prefix => frontend_code.contains(static_prefix),
+    }
+}
+
+// "build" step of a static client: it has to be a html document calling every route
+fn check_frontend_code(frontend_code: &str, api_schema: &[FormattedRouteObject]) -> Vec<String> {
+    let mut frontend_bugs: Vec<String> = Vec::new();
+    let lowercase_code = frontend_code.to_lowercase();
+
+    if !lowercase_code.contains("<html") || !lowercase_code.contains("<script") {
+        frontend_bugs.push("The output is not a HTML document with an inline <script>".to_string());
+    }
+
+    if !frontend_code.contains("API_BASE_URL") {
+        frontend_bugs
+            .push("API_BASE_URL is not declared or not used for the api calls".to_string());
+    }
+
+    for route_obj in api_schema {
+        if !is_route_called(frontend_code, &route_obj.route) {
+            frontend_bugs.push(format!(
+                "Route {} {} is never called by the page",
+                route_obj.method, route_obj.route
+            ));
+        }
+    }
+
+    frontend_bugs
+}
+
+// serve the page locally and make sure the running backend accepts calls from it
+// bugs of the page are returned, an error is a backend problem the page can not fix
+async fn on_validate_frontend(
+    agent: &mut FrontendDeveloperAgent,
+    factsheet: &FactSheet,
+) -> Result<Vec<String>, String> {
+    let frontend_code = factsheet.frontend_code.clone().unwrap_or_default();
+    let api_schema = factsheet.api_endpoint_schema.clone().unwrap_or_default();
+
+    PrintCommand::UnitTest.print_agent_action(
+        agent.attributes.position.as_str(),
+        "Frontend Code Unit Testing: Checking the generated page",
+    );
+
+    let mut frontend_bugs = check_frontend_code(&frontend_code, &api_schema);
+    if !frontend_bugs.is_empty() {
+        return Ok(frontend_bugs);
+    }
+
+    PrintCommand::UnitTest.print_agent_action(
+        agent.attributes.position.as_str(),
+        "Frontend Code Unit Testing: Serving the page next to the running backend",
+    );
+
+    let (frontend_port, frontend_server) = serve_html(frontend_code.clone())
+        .await
+        .expect("Failed to serve the frontend");
+    let frontend_origin = format!("http://127.0.0.1:{}", frontend_port);
+
+    // the backend already passed its own validation, a server that does not come up is not the page's bug
+    let mut backend_server =
+        match start_backend_server(factsheet.external_urls.as_deref().unwrap_or_default()).await {
+            Ok(backend_server) => backend_server,
+            Err(server_error) => {
+                frontend_server.abort();
+                return Err(format!(
+                    "the backend server did not start for the frontend: {}",
+                    server_error
+                ));
+            }
+        };
+    let client = get_client();
+
+    match client.get(format!("{}/", frontend_origin)).send().await {
+        Ok(response) if response.status().as_u16() == 200 => {}
+        _ => frontend_bugs.push("The page could not be served".to_string()),
+    }
+
+    // the browser only lets the page read responses the backend allows for its origin
+    let mut backend_bugs: Vec<String> = Vec::new();
+    for route_obj in api_schema
+        .iter()
+        .filter(|route_obj| route_obj.method == HttpMethod::Get && !route_obj.is_route_dynamic)
+    {
+        let url = format!("{}{}", backend_server.url, route_obj.route);
+
+        PrintCommand::UnitTest.print_agent_action(
+            agent.attributes.position.as_str(),
+            format!("Testing endpoint from the page origin: {}", route_obj.route).as_str(),
+        );
+
+        match client
+            .get(url.as_str())
+            .header("Origin", frontend_origin.as_str())
+            .send()
+            .await
+        {
+            Ok(response) => {
+                let status_code = response.status().as_u16();
+                let is_origin_allowed = response
+                    .headers()
+                    .contains_key("access-control-allow-origin");
+
+                if status_code != 200 {
+                    backend_bugs.push(format!("GET {} returned {}", route_obj.route, status_code));
+                } else if !is_origin_allowed {
+                    backend_bugs.push(format!(
+                        "GET {} does not allow calls from the page origin {} (CORS)",
+                        route_obj.route, frontend_origin
+                    ));
+                }
+            }
+            Err(e) => backend_bugs.push(format!("GET {} failed: {:?}", route_obj.route, e)),
+        }
+    }
+
+    frontend_server.abort();
+    backend_server
+        .kill()
+        .expect("Failed to terminate webserver on completion");
+
+    if !backend_bugs.is_empty() {
+        return Err(backend_bugs.join("\n"));
+    }
+
+    Ok(frontend_bugs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::common::api_schema::SchemaType;
+
+    fn route_obj(route: &str, method: HttpMethod) -> FormattedRouteObject {
+        FormattedRouteObject::new(route, method, SchemaType::None, SchemaType::None)
+    }
+
+    #[test]
+    fn test_check_frontend_code() {
+        let api_schema = vec![
+            route_obj("/item/{id}", HttpMethod::Get),
+            route_obj("/crypto", HttpMethod::Get),
+        ];
+
+        let frontend_code = r#"<!DOCTYPE html><html><body><script>
+            const API_BASE_URL = "http://localhost:8080";
+            fetch(`${API_BASE_URL}/item/${id}`);
+        </script></body></html>"#;
+
+        let frontend_bugs = check_frontend_code(frontend_code, &api_schema);
+
+        assert_eq!(
+            frontend_bugs,
+            vec!["Route GET /crypto is never called by the page".to_string()]
+        );
+        assert_eq!(check_frontend_code("fn main() {}", &[]).len(), 2);
+    }
+
+    #[test]
+    fn test_root_route_is_called() {
+        let api_schema = vec![route_obj("/", HttpMethod::Get)];
+
+        let item_page = r#"<html><script>
+            const API_BASE_URL = "http://localhost:8080";
+            fetch(`${API_BASE_URL}/item`);
+        </script></html>"#;
+        let root_page = r#"<html><script>
+            const API_BASE_URL = "http://localhost:8080";
+            fetch(`${API_BASE_URL}/`);
+        </script></html>"#;
+
+        assert_eq!(
+            check_frontend_code(item_page, &api_schema),
+            vec!["Route GET / is never called by the page".to_string()]
+        );
+        assert!(check_frontend_code(root_page, &api_schema).is_empty());
+    }
+}
@@ -1,3 +1,8 @@
 pub mod agent_traits;
+pub mod auth_agent;
 pub mod backend_agent;
+pub mod code_reviewer_agent;
+pub mod database_agent;
+pub mod frontend_agent;
 pub mod solution_architect_agent;
+pub mod test_engineer_agent;
@@ -1,22 +1,33 @@
 use async_trait_fn::async_trait;
 
-use super::agent_traits::{FactSheet, ProjectScope, SpecialFunctions};
+use super::agent_traits::{FactSheet, FactSheetField, ProjectScope, SpecialFunctions};
 use crate::ai_function::aifunc_architect::{print_project_scope, print_site_urls};
+use crate::model::basic_agents::agent_memory::MemoryStrategy;
 use crate::model::basic_agents::basic_agent_traits::BasicAgentTrait;
 use crate::model::basic_agents::basic_agents::{AgentState, BasicAgent};
 use crate::util::command_line::PrintCommand;
-use crate::util::common::{ai_task_request_decoded, check_status_code};
+use crate::util::common::{ai_task_request_decoded_with_memory, check_status_code};
 use crate::util::provider::get_client;
+use crate::util::run_metrics::record_scope_decoded;
+use tracing::Span;
 
 #[derive(Debug)]
 pub struct SolutionArchitect {
     pub attributes: BasicAgent,
 }
 
+impl Default for SolutionArchitect {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl SolutionArchitect {
     pub fn new() -> Self {
         let solution_architect = BasicAgent {
             memory: Vec::from([]),
+            memory_strategy: MemoryStrategy::SlidingWindow { max_tokens: 2000 },
+            state_span: Span::none(),
             objective: "Gather information and solutions for web developement".to_string(),
             position: "Solution Architect".to_string(),
             state: AgentState::Discovery,
@@ -29,26 +40,29 @@ impl SolutionArchitect {
 
     // retrieve project scope
     async fn call_project_scope(&mut self, factsheet: &mut FactSheet) -> ProjectScope {
-        let ai_response: ProjectScope = ai_task_request_decoded::<ProjectScope>(
+        let ai_response: ProjectScope = ai_task_request_decoded_with_memory::<ProjectScope>(
             print_project_scope,
             format!("{:?}", factsheet.project_description).as_ref(),
-            self.attributes.position.as_ref(),
+            &mut self.attributes,
             stringify!(print_project_scope),
+            false,
         )
         .await;
 
+        record_scope_decoded();
         factsheet.project_scope = Some(ai_response.clone());
-        self.attributes.update_state(AgentState::Finished);
 
         ai_response
     }
 
     async fn call_site_urls(&mut self, factsheet: &mut FactSheet) {
-        let ai_response: Vec<String> = ai_task_request_decoded(
+        // the project scope decided above is part of the conversation history
+        let ai_response: Vec<String> = ai_task_request_decoded_with_memory(
             print_site_urls,
             format!("{:?}", factsheet.project_description).as_ref(),
-            "Solution Architect",
+            &mut self.attributes,
             stringify!(print_site_urls),
+            true,
         )
         .await;
 
@@ -63,12 +77,24 @@ impl SpecialFunctions for SolutionArchitect {
         &self.attributes
     }
 
+    fn get_attributes_mut(&mut self) -> &mut BasicAgent {
+        &mut self.attributes
+    }
+
+    fn reads(&self) -> Vec<FactSheetField> {
+        vec![FactSheetField::ProjectDescription]
+    }
+
+    fn writes(&self) -> Vec<FactSheetField> {
+        vec![FactSheetField::ProjectScope, FactSheetField::ExternalUrls]
+    }
+
     async fn execute_logic(
         &mut self,
         factsheet: &mut FactSheet,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        while self.attributes.state != AgentState::Finished {
-            match self.attributes.state {
+        while !self.attributes.state.is_terminal() {
+            match &self.attributes.state {
                 AgentState::Discovery => {
                     on_discovery_stage(self, factsheet).await;
                 }
@@ -78,8 +104,9 @@ impl SpecialFunctions for SolutionArchitect {
                     self.attributes.update_state(AgentState::Finished);
                 }
 
-                _ => {
-                    self.attributes.update_state(AgentState::Finished);
+                unexpected_state => {
+                    let reason = format!("can not continue from {:?}", unexpected_state);
+                    self.attributes.update_state(AgentState::Failed(reason));
                 }
             }
         }
@@ -91,9 +118,11 @@ impl SpecialFunctions for SolutionArchitect {
 async fn on_discovery_stage(agent: &mut SolutionArchitect, factsheet: &mut FactSheet) {
     let project_scope = agent.call_project_scope(factsheet).await;
 
+    // without external urls there is nothing left to test
     if project_scope.is_external_urls_required {
         agent.call_site_urls(factsheet).await;
-        agent.attributes.update_state(AgentState::UnitTesting);
+    } else {
+        agent.attributes.update_state(AgentState::Finished);
     }
 }
 
@@ -155,10 +184,7 @@ mod tests {
 
         let mut factsheet = FactSheet {
             project_description: Some("Build a full stack website for crypto exchange".to_string()),
-            backend_code: None,
-            api_enpoint_scheme: None,
-            external_urls: None,
-            project_scope: None,
+            ..FactSheet::default()
         };
 
         let _ = solution_architect
@@ -0,0 +1,500 @@
+use async_trait_fn::async_trait;
+
+use super::agent_traits::{FactSheet, FactSheetField, SpecialFunctions};
+use super::auth_agent::run_auth_flow;
+use super::code_reviewer_agent::on_review_and_approve;
+use crate::ai_function::aifunc_backend::print_fixed_code;
+use crate::ai_function::aifunc_testing::print_integration_tests;
+use crate::model::basic_agents::agent_memory::{MemoryStrategy, BUILD_ERROR_PREFIX};
+use crate::model::basic_agents::basic_agent_traits::BasicAgentTrait;
+use crate::model::basic_agents::basic_agents::{AgentState, BasicAgent};
+use crate::model::common::large_language_model::Message;
+use crate::util::code_policy::{check_code_policy, format_policy_violations, CodePolicy};
+use crate::util::command_line::PrintCommand;
+use crate::util::common::{
+    ai_task_request_with_memory, read_backend_code, save_backend_code, save_generated_tests,
+};
+use crate::util::project_runner::{start_backend_server, test_backend_project};
+use crate::util::provider::get_client;
+use crate::util::run_metrics::record_fix_iteration;
+use crate::validation::endpoint_validator::validate_endpoints;
+use tracing::Span;
+
+const TEST_MODULE_DECLARATION: &str = "#[cfg(test)]\nmod generated_tests;\n";
+const TEST_FILE_NAME: &str = "src/generated_tests.rs";
+
+// the file the fix loop rewrites, a broken test can not be fixed by changing main.rs
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FixTarget {
+    BackendCode,
+    TestCode,
+}
+
+#[derive(Debug)]
+pub struct TestEngineerAgent {
+    attributes: BasicAgent,
+    test_code: Option<String>,
+    bug_error: Option<String>,
+    bug_count: u8,
+    fix_target: FixTarget,
+    // main.rs was rewritten, the endpoints are validated again once the tests pass
+    is_backend_changed: bool,
+}
+
+impl Default for TestEngineerAgent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestEngineerAgent {
+    pub fn new() -> Self {
+        let test_engineer_attributes = BasicAgent {
+            memory: Vec::new(),
+            memory_strategy: MemoryStrategy::Summarize {
+                max_tokens: 6000,
+                keep_recent: 2,
+            },
+            state_span: Span::none(),
+            objective: "Write and run integration tests for the backend webserver".to_string(),
+            position: "Test Engineer".to_string(),
+            state: AgentState::Discovery,
+        };
+
+        Self {
+            attributes: test_engineer_attributes,
+            test_code: None,
+            bug_error: None,
+            bug_count: 0,
+            fix_target: FixTarget::BackendCode,
+            is_backend_changed: false,
+        }
+    }
+
+    async fn call_integration_tests(&mut self, factsheet: &mut FactSheet) {
+        let backend_code = read_backend_code();
+
+        let message_context = format!(
+            "BACKEND_CODE: {} \n API_ENDPOINT_SCHEMA: {:?} \n",
+            backend_code, factsheet.api_endpoint_schema
+        );
+
+        let test_code: String = ai_task_request_with_memory(
+            print_integration_tests,
+            &message_context,
+            &mut self.attributes,
+            stringify!(print_integration_tests),
+            false,
+        )
+        .await;
+
+        save_generated_tests(&test_code);
+        self.test_code = Some(test_code);
+
+        // the tests only run if main.rs declares the module
+        let backend_code = declare_test_module(&backend_code);
+        save_backend_code(&backend_code);
+        factsheet.backend_code = Some(backend_code);
+    }
+
+    // failing tests go back into the backend fix loop, the tests are the spec
+    async fn call_fix_buggy_code(&mut self, factsheet: &mut FactSheet) {
+        record_fix_iteration();
+
+        let message_context = format!(
+            "BROKEN_CODE: {:?} \n TEST_CODE: {:?} \n ERROR_BUGS: {:?} \n.
+        THE TEST_CODE IS CORRECT, ONLY FIX THE BROKEN_CODE.
+        THIS FUNCTION ONLY OUTPUTS CODE. JUST OUTPUT THE CODE.",
+            factsheet.backend_code, self.test_code, self.bug_error
+        );
+
+        let fixed_backend_code = ai_task_request_with_memory(
+            print_fixed_code,
+            &message_context,
+            &mut self.attributes,
+            stringify!(print_fixed_code),
+            true,
+        )
+        .await;
+
+        let fixed_backend_code = declare_test_module(&fixed_backend_code);
+        save_backend_code(&fixed_backend_code);
+        factsheet.backend_code = Some(fixed_backend_code);
+        self.is_backend_changed = true;
+    }
+
+    // tests that break the policy or do not compile are rewritten, main.rs stays as it is
+    async fn call_fix_integration_tests(&mut self, factsheet: &mut FactSheet) {
+        record_fix_iteration();
+
+        let message_context = format!(
+            "BACKEND_CODE: {:?} \n API_ENDPOINT_SCHEMA: {:?} \n BROKEN_TEST_CODE: {:?} \n ERROR_BUGS: {:?} \n.
+        THE BACKEND_CODE IS CORRECT, ONLY FIX THE BROKEN_TEST_CODE.",
+            factsheet.backend_code, factsheet.api_endpoint_schema, self.test_code, self.bug_error
+        );
+
+        let fixed_test_code = ai_task_request_with_memory(
+            print_integration_tests,
+            &message_context,
+            &mut self.attributes,
+            stringify!(print_integration_tests),
+            true,
+        )
+        .await;
+
+        save_generated_tests(&fixed_test_code);
+        self.test_code = Some(fixed_test_code);
+    }
+}
+
+#[async_trait]
+impl SpecialFunctions for TestEngineerAgent {
+    fn get_attributes(&self) -> &BasicAgent {
+        &self.attributes
+    }
+
+    fn get_attributes_mut(&mut self) -> &mut BasicAgent {
+        &mut self.attributes
+    }
+
+    fn reads(&self) -> Vec<FactSheetField> {
+        vec![
+            FactSheetField::ProjectScope,
+            FactSheetField::DatabaseCode,
+            FactSheetField::BackendCode,
+            FactSheetField::ApiEndpointSchema,
+        ]
+    }
+
+    fn writes(&self) -> Vec<FactSheetField> {
+        vec![FactSheetField::BackendCode]
+    }
+
+    async fn execute_logic(
+        &mut self,
+        factsheet: &mut FactSheet,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        while !self.attributes.state.is_terminal() {
+            match &self.attributes.state {
+                AgentState::Discovery => {
+                    if factsheet.api_endpoint_schema.is_none() {
+                        PrintCommand::Issue.print_agent_action(
+                            self.attributes.position.as_str(),
+                            "No api endpoint schema in the factsheet, skipping the tests",
+                        );
+                        self.attributes.update_state(AgentState::Finished);
+                        continue;
+                    }
+
+                    self.call_integration_tests(factsheet).await;
+                    self.attributes.update_state(AgentState::UnitTesting);
+                }
+                AgentState::Working => {
+                    match self.fix_target {
+                        FixTarget::BackendCode => self.call_fix_buggy_code(factsheet).await,
+                        FixTarget::TestCode => self.call_fix_integration_tests(factsheet).await,
+                    }
+                    self.attributes.update_state(AgentState::UnitTesting);
+                }
+                AgentState::UnitTesting => {
+                    on_run_integration_tests(self, factsheet).await;
+                }
+                unexpected_state => {
+                    let reason = format!("can not continue from {:?}", unexpected_state);
+                    self.attributes.update_state(AgentState::Failed(reason));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// make sure main.rs contains `#[cfg(test)] mod generated_tests;`
+fn declare_test_module(backend_code: &str) -> String {
+    if backend_code.contains("mod generated_tests;") {
+        return backend_code.to_string();
+    }
+
+    format!("{}\n{}", backend_code.trim_end(), TEST_MODULE_DECLARATION)
+}
+
+// compiler errors point at the file they are in, eg. " --> src/generated_tests.rs:12:5"
+fn is_test_code_error(compile_output: &str) -> bool {
+    compile_output.contains(&format!("--> {}", TEST_FILE_NAME))
+}
+
+// pull the failed test names and their panic output out of `cargo test` stdout
+fn summarize_test_failures(test_output: &str) -> Vec<String> {
+    let mut failures: Vec<String> = Vec::new();
+    let mut current_failure: Option<String> = None;
+
+    for line in test_output.lines() {
+        if let Some(test_name) = line
+            .strip_prefix("---- ")
+            .and_then(|line| line.strip_suffix(" stdout ----"))
+        {
+            failures.extend(current_failure.take());
+            current_failure = Some(format!("{}:", test_name));
+            continue;
+        }
+
+        if line.starts_with("failures:") || line.starts_with("test result:") {
+            failures.extend(current_failure.take());
+            continue;
+        }
+
+        if let Some(failure) = current_failure.as_mut() {
+            if !line.trim().is_empty() {
+                failure.push_str(&format!("\n  {}", line.trim()));
+            }
+        }
+    }
+
+    failures.extend(current_failure);
+    failures
+}
+
+async fn on_run_integration_tests(agent: &mut TestEngineerAgent, factsheet: &FactSheet) {
+    PrintCommand::UnitTest.print_agent_action(
+        agent.attributes.position.as_str(),
+        "Integration Testing: Requesting user approval to run the generated tests.",
+    );
+
+    // cargo test executes AI written code as well
+    let backend_code = read_backend_code();
+    let test_code = agent.test_code.clone().unwrap_or_default();
+
+    // the tests are rewritten first, fixing main.rs against broken tests goes nowhere
+    let code_policy = CodePolicy::for_factsheet(factsheet);
+    let policy_checks = [
+        (FixTarget::TestCode, TEST_FILE_NAME, &test_code),
+        (FixTarget::BackendCode, "src/main.rs", &backend_code),
+    ];
+
+    for (fix_target, file_name, code) in policy_checks {
+        let violations = check_code_policy(code, &code_policy);
+        if violations.is_empty() {
+            continue;
+        }
+
+        PrintCommand::Issue.print_agent_action(
+            agent.attributes.position.as_str(),
+            format!(
+                "Integration Testing: {} breaks the policy, sending it to the fix loop",
+                file_name
+            )
+            .as_str(),
+        );
+        on_tests_failed(
+            agent,
+            fix_target,
+            format!("{}:\n{}", file_name, format_policy_violations(&violations)),
+        );
+        return;
+    }
+
+    let reviewed_files = [
+        ("src/main.rs", backend_code.as_str()),
+        (TEST_FILE_NAME, test_code.as_str()),
+    ];
+    if !on_review_and_approve(&mut agent.attributes, &reviewed_files).await {
+        return;
+    }
+
+    PrintCommand::UnitTest.print_agent_action(
+        agent.attributes.position.as_str(),
+        "Integration Testing: Running cargo test...",
+    );
+
+    let test_command_output = test_backend_project();
+
+    if test_command_output.status.success() {
+        PrintCommand::UnitTest.print_agent_action(
+            agent.attributes.position.as_str(),
+            "Integration Testing: All generated tests passed, Cheers",
+        );
+
+        // a fix for the tests can break what the backend agent already validated
+        if agent.is_backend_changed {
+            if let Err(endpoint_error) = on_validate_fixed_backend(agent, factsheet).await {
+                on_tests_failed(agent, FixTarget::BackendCode, endpoint_error);
+                return;
+            }
+        }
+
+        agent.attributes.update_state(AgentState::Finished);
+        return;
+    }
+
+    let test_stdout = String::from_utf8_lossy(&test_command_output.stdout).to_string();
+    let test_stderr = String::from_utf8_lossy(&test_command_output.stderr).to_string();
+
+    // no failed test means the tests did not even compile
+    let test_failures = summarize_test_failures(&test_stdout);
+    if test_failures.is_empty() {
+        let fix_target = if is_test_code_error(&test_stderr) {
+            FixTarget::TestCode
+        } else {
+            FixTarget::BackendCode
+        };
+
+        PrintCommand::Issue.print_agent_action(
+            agent.attributes.position.as_str(),
+            "Integration Testing: The project does not compile, sending it to the fix loop",
+        );
+        on_tests_failed(agent, fix_target, test_stderr);
+        return;
+    }
+
+    PrintCommand::Issue.print_agent_action(
+        agent.attributes.position.as_str(),
+        format!(
+            "Integration Testing: {} test(s) failed, sending them to the fix loop",
+            test_failures.len()
+        )
+        .as_str(),
+    );
+
+    on_tests_failed(agent, FixTarget::BackendCode, test_failures.join("\n"));
+}
+
+// run the fixed server and call every endpoint again, with a login token once the server has logins
+async fn on_validate_fixed_backend(
+    agent: &mut TestEngineerAgent,
+    factsheet: &FactSheet,
+) -> Result<(), String> {
+    PrintCommand::UnitTest.print_agent_action(
+        agent.attributes.position.as_str(),
+        "Integration Testing: main.rs was fixed, validating the endpoints again",
+    );
+
+    let mut backend_server =
+        start_backend_server(factsheet.external_urls.as_deref().unwrap_or_default()).await?;
+
+    let is_user_login_and_logout = factsheet
+        .project_scope
+        .as_ref()
+        .map(|project_scope| project_scope.is_user_login_and_logout)
+        .unwrap_or(false);
+
+    let client = get_client();
+    let bearer_token = if is_user_login_and_logout {
+        match run_auth_flow(&client, &backend_server.url).await {
+            Ok(token) => Some(token),
+            Err(auth_error) => {
+                backend_server
+                    .kill()
+                    .expect("Failed to terminate webserver after a failed auth flow");
+                return Err(auth_error);
+            }
+        }
+    } else {
+        None
+    };
+
+    let run_report = validate_endpoints(
+        &client,
+        &mut backend_server,
+        factsheet.api_endpoint_schema.as_deref().unwrap_or_default(),
+        agent.attributes.position.as_str(),
+        bearer_token.as_deref(),
+    )
+    .await;
+
+    backend_server
+        .kill()
+        .expect("Failed to terminate webserver after validating the endpoints");
+
+    if run_report.failed_endpoints().is_empty() {
+        return Ok(());
+    }
+
+    PrintCommand::Issue.print_agent_action(
+        agent.attributes.position.as_str(),
+        run_report.failure_summary().as_str(),
+    );
+    Err(run_report.failure_summary())
+}
+
+fn on_tests_failed(agent: &mut TestEngineerAgent, fix_target: FixTarget, bug_error: String) {
+    agent.attributes.add_memory(Message {
+        role: "user".to_string(),
+        content: format!("{} {}", BUILD_ERROR_PREFIX, bug_error),
+    });
+
+    agent.bug_count += 1;
+    agent.bug_error = Some(bug_error);
+    agent.fix_target = fix_target;
+
+    if agent.bug_count > 2 {
+        PrintCommand::Issue.print_agent_action(
+            agent.attributes.position.as_str(),
+            "Integration Testing: Too many bugs to handle",
+        );
+        agent
+            .attributes
+            .update_state(AgentState::Failed("too many bugs to handle".to_string()));
+        return;
+    }
+
+    agent.attributes.update_state(AgentState::Working);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_declare_test_module() {
+        let backend_code = "fn main() {}\n";
+        let declared_code = declare_test_module(backend_code);
+
+        assert!(declared_code.ends_with(TEST_MODULE_DECLARATION));
+        assert_eq!(declare_test_module(&declared_code), declared_code);
+    }
+
+    #[test]
+    fn test_is_test_code_error() {
+        let test_code_error = "error[E0425]: cannot find function `create_app` in this scope\n  --> src/generated_tests.rs:12:23\n";
+        let backend_error = "error[E0308]: mismatched types\n  --> src/main.rs:40:5\n";
+
+        assert!(is_test_code_error(test_code_error));
+        assert!(!is_test_code_error(backend_error));
+    }
+
+    #[test]
+    fn test_summarize_test_failures() {
+        let test_output = r#"
+running 3 tests
+test generated_tests::test_create_item ... ok
+test generated_tests::test_read_item ... FAILED
+test generated_tests::test_delete_item ... FAILED
+
+failures:
+
+---- generated_tests::test_read_item stdout ----
+thread 'generated_tests::test_read_item' panicked at src/generated_tests.rs:30:9:
+assertion `left == right` failed
+  left: 404
+ right: 200
+
+---- generated_tests::test_delete_item stdout ----
+thread 'generated_tests::test_delete_item' panicked at src/generated_tests.rs:52:9:
+expected 200
+
+failures:
+    generated_tests::test_read_item
+    generated_tests::test_delete_item
+
+test result: FAILED. 1 passed; 2 failed; 0 ignored; 0 measured; 0 filtered out
+"#;
+
+        let failures = summarize_test_failures(test_output);
+
+        assert_eq!(failures.len(), 2);
+        assert!(failures[0].starts_with("generated_tests::test_read_item:"));
+        assert!(failures[0].contains("left: 404"));
+        assert!(failures[1].ends_with("expected 200"));
+    }
+}
@@ -0,0 +1,191 @@
+use crate::model::common::large_language_model::Message;
+
+// prefix of the memory entries holding compiler output, these are never summarized
+pub const BUILD_ERROR_PREFIX: &str = "BUILD_ERROR:";
+pub const MEMORY_SUMMARY_PREFIX: &str = "MEMORY_SUMMARY:";
+
+// roles and separators added by the chat completion api for every message
+const TOKENS_PER_MESSAGE: usize = 4;
+
+// how an agent keeps its memory within the context window of the model
+#[derive(Debug, Clone, PartialEq)]
+pub enum MemoryStrategy {
+    // keep every exchange verbatim
+    Unbounded,
+    // drop the oldest exchanges once the memory grows over max_tokens
+    SlidingWindow {
+        max_tokens: usize,
+    },
+    // compress the oldest exchanges into a single summary once the memory grows over max_tokens
+    // the last keep_recent messages are always kept verbatim
+    Summarize {
+        max_tokens: usize,
+        keep_recent: usize,
+    },
+}
+
+impl Default for MemoryStrategy {
+    fn default() -> Self {
+        MemoryStrategy::Summarize {
+            max_tokens: 6000,
+            keep_recent: 2,
+        }
+    }
+}
+
+// memory split into what can be compressed and what has to stay verbatim
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemoryCompaction {
+    pub compressed: Vec<Message>,
+    pub kept: Vec<Message>,
+}
+
+// rough estimate of ~4 characters per token, close enough for english text and code
+pub fn count_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+pub fn count_memory_tokens(memory: &[Message]) -> usize {
+    memory
+        .iter()
+        .map(|message| count_tokens(&message.content) + TOKENS_PER_MESSAGE)
+        .sum()
+}
+
+// the newest code (assistant response) and the newest compiler errors are pinned
+fn pinned_indexes(memory: &[Message], keep_recent: usize) -> Vec<usize> {
+    let mut pinned: Vec<usize> = (memory.len().saturating_sub(keep_recent)..memory.len()).collect();
+
+    if let Some(index) = memory
+        .iter()
+        .rposition(|message| message.role == "assistant")
+    {
+        pinned.push(index);
+    }
+
+    if let Some(index) = memory
+        .iter()
+        .rposition(|message| message.content.starts_with(BUILD_ERROR_PREFIX))
+    {
+        pinned.push(index);
+    }
+
+    pinned
+}
+
+// decide which messages to compress, returns None if the memory already fits
+pub fn plan_memory_compaction(
+    memory: &[Message],
+    strategy: &MemoryStrategy,
+) -> Option<MemoryCompaction> {
+    let (max_tokens, keep_recent) = match strategy {
+        MemoryStrategy::Unbounded => return None,
+        MemoryStrategy::SlidingWindow { max_tokens } => (*max_tokens, 0),
+        MemoryStrategy::Summarize {
+            max_tokens,
+            keep_recent,
+        } => (*max_tokens, *keep_recent),
+    };
+
+    if count_memory_tokens(memory) <= max_tokens {
+        return None;
+    }
+
+    let pinned = pinned_indexes(memory, keep_recent);
+    let mut remaining_tokens = count_memory_tokens(memory);
+    let mut compressed: Vec<Message> = Vec::new();
+    let mut kept: Vec<Message> = Vec::new();
+
+    for (index, message) in memory.iter().enumerate() {
+        // a sliding window only drops as much as it needs to, oldest first
+        let is_within_window = matches!(strategy, MemoryStrategy::SlidingWindow { .. })
+            && remaining_tokens <= max_tokens;
+
+        if pinned.contains(&index) || is_within_window {
+            kept.push(message.clone());
+        } else {
+            remaining_tokens -= count_memory_tokens(std::slice::from_ref(message));
+            compressed.push(message.clone());
+        }
+    }
+
+    if compressed.is_empty() {
+        return None;
+    }
+
+    Some(MemoryCompaction { compressed, kept })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(role: &str, content: &str) -> Message {
+        Message {
+            role: role.to_string(),
+            content: content.to_string(),
+        }
+    }
+
+    fn fix_loop_memory() -> Vec<Message> {
+        vec![
+            message("system", &"write the initial code ".repeat(40)),
+            message("assistant", &"fn main() { old() }".repeat(40)),
+            message("user", &format!("{} old error", BUILD_ERROR_PREFIX)),
+            message("system", &"fix the broken code ".repeat(40)),
+            message("assistant", &"fn main() { newest() }".repeat(40)),
+            message("user", &format!("{} newest error", BUILD_ERROR_PREFIX)),
+        ]
+    }
+
+    #[test]
+    fn test_count_memory_tokens() {
+        assert_eq!(count_tokens("abcdefgh"), 2);
+        assert_eq!(count_tokens("abc"), 1);
+        assert_eq!(count_memory_tokens(&[message("user", "abcd")]), 5);
+    }
+
+    #[test]
+    fn test_memory_within_limit_is_untouched() {
+        let strategy = MemoryStrategy::Summarize {
+            max_tokens: 100_000,
+            keep_recent: 2,
+        };
+
+        assert_eq!(plan_memory_compaction(&fix_loop_memory(), &strategy), None);
+        assert_eq!(
+            plan_memory_compaction(&fix_loop_memory(), &MemoryStrategy::Unbounded),
+            None
+        );
+    }
+
+    #[test]
+    fn test_summarize_keeps_newest_code_and_errors() {
+        let strategy = MemoryStrategy::Summarize {
+            max_tokens: 100,
+            keep_recent: 1,
+        };
+
+        let compaction = plan_memory_compaction(&fix_loop_memory(), &strategy).unwrap();
+
+        assert_eq!(compaction.compressed.len(), 4);
+        assert_eq!(compaction.kept.len(), 2);
+        assert!(compaction.kept[0].content.contains("newest()"));
+        assert!(compaction.kept[1].content.contains("newest error"));
+    }
+
+    #[test]
+    fn test_sliding_window_drops_oldest_first() {
+        let memory = fix_loop_memory();
+        let newest_tokens = count_memory_tokens(&memory[3..]);
+
+        let strategy = MemoryStrategy::SlidingWindow {
+            max_tokens: newest_tokens,
+        };
+
+        let compaction = plan_memory_compaction(&memory, &strategy).unwrap();
+
+        assert_eq!(compaction.compressed, memory[..3].to_vec());
+        assert_eq!(compaction.kept, memory[3..].to_vec());
+    }
+}
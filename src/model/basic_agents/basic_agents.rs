@@ -1,20 +1,70 @@
+use super::agent_memory::MemoryStrategy;
 use super::basic_agent_traits::BasicAgentTrait;
+use crate::model::agents::agent_traits::FactSheetField;
 use crate::model::common::large_language_model::Message;
+use crate::util::event_bus::{publish, AgentEvent};
+use serde::Serialize;
+use tracing::{info_span, Span};
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum AgentState {
     Discovery,
     Working,
     UnitTesting,
+    // the code reviewer summarizes the risks of the generated code
+    Reviewing,
+    // the reviewed code waits for the user's go
+    AwaitingApproval,
+    // an agent it depends on did not deliver this field, set by the manager
+    Blocked(FactSheetField),
+    Failed(String),
     Finished,
 }
 
+impl AgentState {
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, AgentState::Finished | AgentState::Failed(_))
+    }
+
+    // every move an agent is allowed to make, anything else is a bug in the agent
+    pub fn can_transition_to(&self, next_state: &AgentState) -> bool {
+        match (self, next_state) {
+            (AgentState::Finished | AgentState::Failed(_), _) => false,
+            (current_state, next_state) if current_state == next_state => true,
+            (_, AgentState::Failed(_)) => true,
+            (
+                AgentState::Discovery,
+                AgentState::Working
+                | AgentState::UnitTesting
+                | AgentState::Blocked(_)
+                | AgentState::Finished,
+            ) => true,
+            (AgentState::Blocked(_), AgentState::Discovery) => true,
+            (AgentState::Working, AgentState::UnitTesting | AgentState::Finished) => true,
+            (
+                AgentState::UnitTesting,
+                AgentState::Working
+                | AgentState::Reviewing
+                | AgentState::AwaitingApproval
+                | AgentState::Finished,
+            ) => true,
+            (AgentState::Reviewing, AgentState::AwaitingApproval) => true,
+            (AgentState::AwaitingApproval, AgentState::UnitTesting) => true,
+            _ => false,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct BasicAgent {
     pub objective: String,
     pub position: String,
     pub state: AgentState,
     pub memory: Vec<Message>,
+    pub memory_strategy: MemoryStrategy,
+    // open while the agent is in its current state, closing it records how long that took
+    pub state_span: Span,
 }
 
 impl BasicAgentTrait for BasicAgent {
@@ -22,6 +72,10 @@ impl BasicAgentTrait for BasicAgent {
         &self.memory
     }
 
+    fn add_memory(&mut self, message: Message) {
+        self.memory.push(message);
+    }
+
     fn get_objective(&self) -> &String {
         &self.objective
     }
@@ -39,11 +93,69 @@ impl BasicAgentTrait for BasicAgent {
             objective,
             position,
             memory: Vec::from([]),
+            memory_strategy: MemoryStrategy::default(),
             state: AgentState::Discovery,
+            state_span: Span::none(),
         }
     }
 
     fn update_state(&mut self, new_state: AgentState) {
+        // finished and failed agents stay that way, the manager starts a new agent to retry
+        if self.state.is_terminal() || self.state == new_state {
+            return;
+        }
+
+        let new_state = if self.state.can_transition_to(&new_state) {
+            new_state
+        } else {
+            AgentState::Failed(format!(
+                "invalid state transition from {:?} to {:?}",
+                self.state, new_state
+            ))
+        };
+
+        publish(AgentEvent::StateChanged {
+            agent: self.position.clone(),
+            state: new_state.clone(),
+        });
+
+        self.state_span = if new_state.is_terminal() {
+            Span::none()
+        } else {
+            info_span!(
+                "agent_state",
+                agent.position = %self.position,
+                agent.state = ?new_state
+            )
+        };
+
         self.state = new_state;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_state() {
+        let mut agent = BasicAgent::new("build things".to_string(), "Tester".to_string());
+
+        agent.update_state(AgentState::UnitTesting);
+        agent.update_state(AgentState::Reviewing);
+        agent.update_state(AgentState::AwaitingApproval);
+        agent.update_state(AgentState::UnitTesting);
+        assert_eq!(agent.state, AgentState::UnitTesting);
+
+        agent.update_state(AgentState::Blocked(FactSheetField::BackendCode));
+        assert_eq!(
+            agent.state,
+            AgentState::Failed(
+                "invalid state transition from UnitTesting to Blocked(BackendCode)".to_string()
+            )
+        );
+
+        agent.update_state(AgentState::Finished);
+        assert!(matches!(agent.state, AgentState::Failed(_)));
+    }
+}
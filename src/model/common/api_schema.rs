@@ -0,0 +1,273 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(try_from = "String", rename_all = "lowercase")]
+pub enum HttpMethod {
+    Get,
+    Post,
+    Put,
+    Patch,
+    Delete,
+    Head,
+    Options,
+}
+
+impl HttpMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HttpMethod::Get => "GET",
+            HttpMethod::Post => "POST",
+            HttpMethod::Put => "PUT",
+            HttpMethod::Patch => "PATCH",
+            HttpMethod::Delete => "DELETE",
+            HttpMethod::Head => "HEAD",
+            HttpMethod::Options => "OPTIONS",
+        }
+    }
+}
+
+// the llm writes "get", "GET" or "Get"
+impl TryFrom<String> for HttpMethod {
+    type Error = String;
+
+    fn try_from(method: String) -> Result<Self, Self::Error> {
+        match method.trim().to_lowercase().as_str() {
+            "get" => Ok(HttpMethod::Get),
+            "post" => Ok(HttpMethod::Post),
+            "put" => Ok(HttpMethod::Put),
+            "patch" => Ok(HttpMethod::Patch),
+            "delete" => Ok(HttpMethod::Delete),
+            "head" => Ok(HttpMethod::Head),
+            "options" => Ok(HttpMethod::Options),
+            _ => Err(format!("unknown http method: {}", method)),
+        }
+    }
+}
+
+impl fmt::Display for HttpMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+// the type language of request bodies and responses in the api schema
+// eg. { "id": "number", "name": "string", "tags": ["string"] }
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(from = "Value", into = "Value")]
+pub enum SchemaType {
+    // "None", there is no body
+    None,
+    // "not_provided", the schema does not say what the body looks like
+    NotProvided,
+    Number,
+    Float,
+    Bool,
+    String,
+    Array(Box<SchemaType>),
+    Object(BTreeMap<String, SchemaType>),
+    // a field of an object that may be missing or null, written as "key?"
+    Optional(Box<SchemaType>),
+    // a type name the language does not know, kept as written
+    Other(String),
+}
+
+impl SchemaType {
+    // no body at all, or none that is described
+    pub fn is_empty(&self) -> bool {
+        matches!(self, SchemaType::None | SchemaType::NotProvided)
+    }
+}
+
+// every json value is a valid schema, unknown names end up in Other
+impl From<Value> for SchemaType {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Null => SchemaType::None,
+            Value::Bool(_) => SchemaType::Bool,
+            Value::Number(number) if number.is_f64() => SchemaType::Float,
+            Value::Number(_) => SchemaType::Number,
+            Value::String(type_name) => match type_name.trim().to_lowercase().as_str() {
+                "none" | "null" | "" => SchemaType::None,
+                "not_provided" => SchemaType::NotProvided,
+                "number" | "integer" | "int" | "u64" | "i64" | "u32" | "i32" | "usize" => {
+                    SchemaType::Number
+                }
+                "float" | "f64" | "f32" => SchemaType::Float,
+                "bool" | "boolean" => SchemaType::Bool,
+                "string" => SchemaType::String,
+                "object" => SchemaType::Object(BTreeMap::new()),
+                "array" => SchemaType::Array(Box::new(SchemaType::NotProvided)),
+                _ => SchemaType::Other(type_name),
+            },
+            // the first item describes every element
+            Value::Array(items) => SchemaType::Array(Box::new(
+                items
+                    .into_iter()
+                    .next()
+                    .map(SchemaType::from)
+                    .unwrap_or(SchemaType::NotProvided),
+            )),
+            Value::Object(fields) => SchemaType::Object(
+                fields
+                    .into_iter()
+                    .map(|(key, field_schema)| match key.strip_suffix('?') {
+                        Some(optional_key) => (
+                            optional_key.to_string(),
+                            SchemaType::Optional(Box::new(SchemaType::from(field_schema))),
+                        ),
+                        None => (key, SchemaType::from(field_schema)),
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+// written back in the same language the llm uses
+impl From<SchemaType> for Value {
+    fn from(schema: SchemaType) -> Self {
+        match schema {
+            SchemaType::None => json!("None"),
+            SchemaType::NotProvided => json!("not_provided"),
+            SchemaType::Number => json!("number"),
+            SchemaType::Float => json!("float"),
+            SchemaType::Bool => json!("bool"),
+            SchemaType::String => json!("string"),
+            SchemaType::Array(item_schema) => json!([Value::from(*item_schema)]),
+            SchemaType::Object(fields) => Value::Object(
+                fields
+                    .into_iter()
+                    .map(|(key, field_schema)| match field_schema {
+                        SchemaType::Optional(inner_schema) => {
+                            (format!("{}?", key), Value::from(*inner_schema))
+                        }
+                        field_schema => (key, Value::from(field_schema)),
+                    })
+                    .collect(),
+            ),
+            // only object fields can be marked optional
+            SchemaType::Optional(inner_schema) => Value::from(*inner_schema),
+            SchemaType::Other(type_name) => json!(type_name),
+        }
+    }
+}
+
+// "/item/{id}/tags/{tag}" -> ["id", "tag"]
+pub fn parse_path_params(route: &str) -> Vec<String> {
+    route
+        .split('/')
+        .filter_map(|segment| segment.strip_prefix('{')?.strip_suffix('}'))
+        .map(|param| param.to_string())
+        .collect()
+}
+
+// one endpoint of the generated webserver
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(try_from = "RouteObjectInput")]
+pub struct FormattedRouteObject {
+    pub route: String,
+    pub is_route_dynamic: bool,
+    pub path_params: Vec<String>,
+    pub method: HttpMethod,
+    pub request_body: SchemaType,
+    pub response: SchemaType,
+}
+
+impl FormattedRouteObject {
+    pub fn new(
+        route: &str,
+        method: HttpMethod,
+        request_body: SchemaType,
+        response: SchemaType,
+    ) -> Self {
+        let path_params = parse_path_params(route);
+
+        Self {
+            route: route.to_string(),
+            is_route_dynamic: !path_params.is_empty(),
+            path_params,
+            method,
+            request_body,
+            response,
+        }
+    }
+}
+
+// what the llm prints, "is_route_dynamic": "true" and missing bodies included
+#[derive(Deserialize)]
+struct RouteObjectInput {
+    route: String,
+    method: HttpMethod,
+    #[serde(default)]
+    request_body: Option<SchemaType>,
+    #[serde(default)]
+    response: Option<SchemaType>,
+}
+
+impl TryFrom<RouteObjectInput> for FormattedRouteObject {
+    type Error = String;
+
+    // the flags and path params come from the route itself, the llm's "is_route_dynamic" is not trusted
+    fn try_from(input: RouteObjectInput) -> Result<Self, Self::Error> {
+        Ok(FormattedRouteObject::new(
+            &input.route,
+            input.method,
+            input.request_body.unwrap_or(SchemaType::None),
+            input.response.unwrap_or(SchemaType::NotProvided),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lenient_route_object() {
+        let route_obj: FormattedRouteObject = serde_json::from_str(
+            r#"{
+              "route": "/item/{id}",
+              "is_route_dynamic": "true",
+              "method": "GET",
+              "request_body": "None",
+              "response": { "id": "number", "name": "string", "tags": ["string"], "price": 1.5, "note?": "string" }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(route_obj.method, HttpMethod::Get);
+        assert!(route_obj.is_route_dynamic);
+        assert_eq!(route_obj.path_params, vec!["id".to_string()]);
+        assert_eq!(route_obj.request_body, SchemaType::None);
+        assert_eq!(
+            route_obj.response,
+            SchemaType::Object(BTreeMap::from([
+                ("id".to_string(), SchemaType::Number),
+                ("name".to_string(), SchemaType::String),
+                (
+                    "note".to_string(),
+                    SchemaType::Optional(Box::new(SchemaType::String))
+                ),
+                ("price".to_string(), SchemaType::Float),
+                (
+                    "tags".to_string(),
+                    SchemaType::Array(Box::new(SchemaType::String))
+                ),
+            ]))
+        );
+
+        // a saved schema reads back the same
+        let saved_route_obj: FormattedRouteObject =
+            serde_json::from_str(&serde_json::to_string(&route_obj).unwrap()).unwrap();
+        assert_eq!(saved_route_obj, route_obj);
+
+        assert!(serde_json::from_str::<FormattedRouteObject>(
+            r#"{ "route": "/item", "method": "fetch" }"#
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,95 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+// output of an agent that has no field of its own on the factsheet, stored under its key
+pub trait FactSheetExtension: Serialize + DeserializeOwned {
+    // namespaced by the publishing crate, eg. "acme_lint.report"
+    const KEY: &'static str;
+}
+
+// extensions are kept as json so factsheets with outputs of unknown agents still round-trip
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(transparent)]
+pub struct FactSheetExtensions(BTreeMap<String, Value>);
+
+impl FactSheetExtensions {
+    // an error when the stored value does not decode, eg. written by another version of the agent
+    pub fn get<T: FactSheetExtension>(&self) -> Result<Option<T>, serde_json::Error> {
+        self.0
+            .get(T::KEY)
+            .map(|value| serde_json::from_value(value.clone()))
+            .transpose()
+    }
+
+    pub fn insert<T: FactSheetExtension>(
+        &mut self,
+        extension: &T,
+    ) -> Result<(), serde_json::Error> {
+        let value = serde_json::to_value(extension)?;
+        self.0.insert(T::KEY.to_string(), value);
+        Ok(())
+    }
+
+    pub fn remove<T: FactSheetExtension>(&mut self) {
+        self.0.remove(T::KEY);
+    }
+
+    pub fn get_raw(&self, key: &str) -> Option<&Value> {
+        self.0.get(key)
+    }
+
+    // copy one key as it is, the manager does not know the types of plugin outputs
+    pub fn copy_raw(&mut self, other: &FactSheetExtensions, key: &str) {
+        match other.0.get(key) {
+            Some(value) => self.0.insert(key.to_string(), value.clone()),
+            None => self.0.remove(key),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct LintReport {
+        warnings: Vec<String>,
+    }
+
+    impl FactSheetExtension for LintReport {
+        const KEY: &'static str = "lint.report";
+    }
+
+    #[test]
+    fn test_factsheet_extensions() {
+        let mut extensions = FactSheetExtensions::default();
+        assert_eq!(extensions.get::<LintReport>().unwrap(), None);
+
+        let report = LintReport {
+            warnings: vec!["unused import".to_string()],
+        };
+        extensions.insert(&report).unwrap();
+
+        let round_trip: FactSheetExtensions =
+            serde_json::from_str(&serde_json::to_string(&extensions).unwrap()).unwrap();
+        assert_eq!(round_trip.get::<LintReport>().unwrap(), Some(report));
+        assert_eq!(
+            round_trip.get_raw("lint.report"),
+            Some(&json!({ "warnings": ["unused import"] }))
+        );
+
+        let mut merged = FactSheetExtensions::default();
+        merged.copy_raw(&round_trip, LintReport::KEY);
+        assert_eq!(merged, round_trip);
+        merged.copy_raw(&FactSheetExtensions::default(), LintReport::KEY);
+        assert_eq!(merged, FactSheetExtensions::default());
+
+        let malformed: FactSheetExtensions =
+            serde_json::from_value(json!({ "lint.report": { "warnings": "unused import" } }))
+                .unwrap();
+        assert!(malformed.get::<LintReport>().is_err());
+    }
+}
@@ -1,6 +1,11 @@
 use serde::{Deserialize, Serialize};
+use std::env;
 
-#[derive(Debug, Serialize, Clone)]
+const DEFAULT_PROVIDER_NAME: &str = "openai";
+const DEFAULT_PROVIDER_URL: &str = "https://api.openai.com/v1/chat/completions";
+const DEFAULT_MODEL: &str = "gpt-4";
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Message {
     pub role: String,
     pub content: String,
@@ -23,7 +28,37 @@ pub struct ChatCompletionChoice {
     pub message: ChatCompletionMessage,
 }
 
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct ChatCompletionUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ChatCompletionResponse {
     pub choices: Vec<ChatCompletionChoice>,
+    pub usage: Option<ChatCompletionUsage>,
+}
+
+// chat completion provider used by call_gpt
+// any OpenAI compatible endpoint can be plugged in here
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LlmProvider {
+    pub name: String,
+    pub url: String,
+    pub model: String,
+    pub temperature: f32,
+}
+
+impl LlmProvider {
+    // read the provider from environment, falling back to OpenAI gpt-4
+    pub fn from_env() -> Self {
+        Self {
+            name: env::var("LLM_PROVIDER_NAME").unwrap_or(DEFAULT_PROVIDER_NAME.to_string()),
+            url: env::var("LLM_PROVIDER_URL").unwrap_or(DEFAULT_PROVIDER_URL.to_string()),
+            model: env::var("LLM_MODEL").unwrap_or(DEFAULT_MODEL.to_string()),
+            temperature: 0.1, // the less the temperature lesser the exploration done by open ai
+        }
+    }
 }
@@ -1 +1,3 @@
+pub mod api_schema;
+pub mod factsheet_extensions;
 pub mod large_language_model;
@@ -1,7 +1,27 @@
-use crate::model::common::large_language_model::{ChatCompletion, ChatCompletionResponse, Message};
+use crate::model::common::large_language_model::{
+    ChatCompletion, ChatCompletionResponse, LlmProvider, Message,
+};
+use crate::util::run_metrics::record_token_usage;
 use dotenv::dotenv;
 use reqwest::header::{HeaderMap, HeaderValue};
 use std::env;
+use std::sync::OnceLock;
+use tracing::Span;
+
+static LLM_PROVIDER: OnceLock<LlmProvider> = OnceLock::new();
+
+// override the provider before the first call (eg. from `hoppity eval --model ...`)
+pub fn set_llm_provider(provider: LlmProvider) -> Result<(), LlmProvider> {
+    LLM_PROVIDER.set(provider)
+}
+
+// provider used for every chat completion in this process
+pub fn get_llm_provider() -> &'static LlmProvider {
+    LLM_PROVIDER.get_or_init(|| {
+        dotenv().ok();
+        LlmProvider::from_env()
+    })
+}
 
 // call large language model (here - GPT-3.5-turbo)
 pub async fn call_gpt(messages: Vec<Message>) -> Result<String, Box<dyn std::error::Error + Send>> {
@@ -16,7 +36,8 @@ pub async fn call_gpt(messages: Vec<Message>) -> Result<String, Box<dyn std::err
         env::var("OPEN_AI_ORG_ID").expect("Failed to find OPEN_AI_ORG_ID from environment file");
 
     // confirm endpoint (chat completion)
-    let url: &str = "https://api.openai.com/v1/chat/completions";
+    let provider = get_llm_provider();
+    let url: &str = provider.url.as_str();
 
     // create headers
     let mut headers = HeaderMap::new();
@@ -42,9 +63,9 @@ pub async fn call_gpt(messages: Vec<Message>) -> Result<String, Box<dyn std::err
 
     // create payload for our chat completion api
     let chat_completion_payload = ChatCompletion {
-        model: "gpt-4".to_string(), // provide other model varients if you want eg. gpt-3.5-turbo
+        model: provider.model.clone(), // provide other model varients via LLM_MODEL eg. gpt-3.5-turbo
         messages,
-        temperature: 0.1, // the less the temperature lesser the exploration done by open ai
+        temperature: provider.temperature,
     };
 
     // test api call
@@ -58,6 +79,16 @@ pub async fn call_gpt(messages: Vec<Message>) -> Result<String, Box<dyn std::err
         .await
         .map_err(|e| -> Box<dyn std::error::Error + Send> { Box::new(e) })?;
 
+    if let Some(usage) = &raw_response.usage {
+        record_token_usage(usage);
+
+        // fields of the llm_call span the request runs in
+        let llm_call_span = Span::current();
+        llm_call_span.record("llm.prompt_tokens", usage.prompt_tokens);
+        llm_call_span.record("llm.completion_tokens", usage.completion_tokens);
+        llm_call_span.record("llm.total_tokens", usage.total_tokens);
+    }
+
     Ok(raw_response.choices[0].message.content.clone())
 }
 
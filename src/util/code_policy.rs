@@ -0,0 +1,812 @@
+use std::collections::{HashMap, HashSet};
+
+use proc_macro2::{Spacing, TokenStream, TokenTree};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+use syn::{
+    Expr, ExprCall, ExprMethodCall, ExprUnsafe, ImplItemFn, ItemConst, ItemExternCrate, ItemFn,
+    ItemForeignMod, ItemImpl, ItemMod, ItemStatic, ItemTrait, ItemUse, Lit, Macro, Path, Token,
+    UseTree,
+};
+
+use crate::model::agents::agent_traits::FactSheet;
+
+// crates the backend prompts tell the model it may use
+pub const BACKEND_ALLOWED_CRATES: [&str; 7] = [
+    "reqwest",
+    "serde",
+    "serde_json",
+    "tokio",
+    "actix-web",
+    "async-trait",
+    "actix_cors",
+];
+pub const DATABASE_ALLOWED_CRATES: [&str; 1] = ["rusqlite"];
+pub const AUTH_ALLOWED_CRATES: [&str; 3] = ["argon2", "password_hash", "uuid"];
+pub const ALLOWED_DATA_DIR: &str = "data/";
+
+const BUILTIN_ROOTS: [&str; 7] = ["std", "core", "alloc", "crate", "self", "super", "Self"];
+const PRIMITIVE_TYPES: [&str; 17] = [
+    "bool", "char", "str", "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64",
+    "i128", "isize", "f32", "f64",
+];
+// fs functions that take two paths, every other one takes the path first
+const TWO_PATH_FS_FUNCTIONS: [&str; 3] = ["copy", "rename", "hard_link"];
+// functions `use std::fs::*` brings in scope without a path
+const STD_FS_FUNCTIONS: [&str; 16] = [
+    "canonicalize",
+    "copy",
+    "create_dir",
+    "create_dir_all",
+    "hard_link",
+    "metadata",
+    "read",
+    "read_dir",
+    "read_link",
+    "read_to_string",
+    "remove_dir",
+    "remove_dir_all",
+    "remove_file",
+    "rename",
+    "set_permissions",
+    "write",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodePolicy {
+    pub allowed_crates: Vec<String>,
+    pub allowed_data_dir: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyViolation {
+    pub line: usize,
+    pub rule: String,
+    pub description: String,
+}
+
+impl CodePolicy {
+    pub fn backend() -> Self {
+        Self {
+            allowed_crates: BACKEND_ALLOWED_CRATES
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+            allowed_data_dir: ALLOWED_DATA_DIR.to_string(),
+        }
+    }
+
+    // the database and authentication agents install a few more crates
+    pub fn for_factsheet(factsheet: &FactSheet) -> Self {
+        let mut policy = Self::backend();
+
+        if factsheet.database_code.is_some() {
+            policy
+                .allowed_crates
+                .extend(DATABASE_ALLOWED_CRATES.iter().map(|name| name.to_string()));
+        }
+
+        let is_user_login_and_logout = factsheet
+            .project_scope
+            .as_ref()
+            .map(|project_scope| project_scope.is_user_login_and_logout)
+            .unwrap_or(false);
+
+        if is_user_login_and_logout {
+            policy
+                .allowed_crates
+                .extend(AUTH_ALLOWED_CRATES.iter().map(|name| name.to_string()));
+        }
+
+        policy
+    }
+
+    fn is_crate_allowed(&self, crate_name: &str) -> bool {
+        self.allowed_crates
+            .iter()
+            .any(|allowed| allowed.replace('-', "_") == crate_name)
+    }
+
+    fn is_path_in_data_dir(&self, path: &str) -> bool {
+        let path = path.strip_prefix("./").unwrap_or(path);
+        let data_dir = self.allowed_data_dir.trim_end_matches('/');
+
+        !path.contains("..") && (path == data_dir || path.starts_with(&self.allowed_data_dir))
+    }
+}
+
+impl PolicyViolation {
+    fn new(span: proc_macro2::Span, rule: &str, description: String) -> Self {
+        Self {
+            line: span.start().line,
+            rule: rule.to_string(),
+            description,
+        }
+    }
+}
+
+// format violations the way print_fixed_code expects ERROR_BUGS
+pub fn format_policy_violations(violations: &[PolicyViolation]) -> String {
+    violations
+        .iter()
+        .map(|violation| {
+            format!(
+                "POLICY_VIOLATION ({}) at line {}: {}",
+                violation.rule, violation.line, violation.description
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn path_segments(path: &Path) -> Vec<String> {
+    path.segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect()
+}
+
+// every path imported by a use tree, eg. `use a::{b, c::d}` -> [a, b], [a, c, d]
+fn use_tree_paths(use_tree: &UseTree, prefix: Vec<String>, paths: &mut Vec<Vec<String>>) {
+    let with_segment = |segment: String| {
+        let mut path = prefix.clone();
+        path.push(segment);
+        path
+    };
+
+    match use_tree {
+        UseTree::Path(use_path) => use_tree_paths(
+            &use_path.tree,
+            with_segment(use_path.ident.to_string()),
+            paths,
+        ),
+        UseTree::Name(use_name) => paths.push(with_segment(use_name.ident.to_string())),
+        UseTree::Rename(use_rename) => paths.push(with_segment(use_rename.ident.to_string())),
+        UseTree::Glob(_) => paths.push(with_segment("*".to_string())),
+        UseTree::Group(use_group) => use_group
+            .items
+            .iter()
+            .for_each(|item| use_tree_paths(item, prefix.clone(), paths)),
+    }
+}
+
+fn is_process_spawn(segments: &[String]) -> bool {
+    segments
+        .windows(2)
+        .any(|window| window[0] == "process" && (window[1] == "Command" || window[1] == "*"))
+}
+
+// std::fs::write(..), fs::File::create(..), File::open(..)
+fn is_fs_call(segments: &[String]) -> bool {
+    let function_name = segments.last().map(String::as_str).unwrap_or_default();
+
+    segments.iter().rev().skip(1).any(|segment| segment == "fs")
+        || (segments.len() >= 2
+            && segments[segments.len() - 2] == "File"
+            && (function_name == "create" || function_name == "open"))
+}
+
+// paths written in macro tokens, eg. json!({ "a": fs::read("x") }) -> [fs, read]
+fn macro_token_paths(tokens: TokenStream, paths: &mut Vec<(Vec<String>, proc_macro2::Span)>) {
+    let mut path: Vec<String> = Vec::new();
+    let mut path_span = proc_macro2::Span::call_site();
+    // `::` is two ':' puncts, the first one joint
+    let mut colon_count = 0;
+
+    for token in tokens {
+        match token {
+            TokenTree::Ident(ident) => {
+                if colon_count != 2 && !path.is_empty() {
+                    paths.push((std::mem::take(&mut path), path_span));
+                }
+                if path.is_empty() {
+                    path_span = ident.span();
+                }
+                path.push(ident.to_string());
+                colon_count = 0;
+            }
+            TokenTree::Punct(punct)
+                if punct.as_char() == ':'
+                    && !path.is_empty()
+                    && (colon_count == 1 || punct.spacing() == Spacing::Joint) =>
+            {
+                colon_count += 1;
+            }
+            other => {
+                if !path.is_empty() {
+                    paths.push((std::mem::take(&mut path), path_span));
+                }
+                colon_count = 0;
+
+                if let TokenTree::Group(group) = other {
+                    macro_token_paths(group.stream(), paths);
+                }
+            }
+        }
+    }
+
+    if !path.is_empty() {
+        paths.push((path, path_span));
+    }
+}
+
+// names declared in the file: modules, use imports, use renames and string constants
+#[derive(Default)]
+struct LocalNames {
+    names: HashSet<String>,
+    // `use std::process as p` -> p: [std, process], `use std::fs::write` -> write: [std, fs, write]
+    aliases: HashMap<String, Vec<String>>,
+    // `use std::fs::*` -> [std, fs]
+    globs: Vec<Vec<String>>,
+    str_consts: HashMap<String, String>,
+}
+
+impl LocalNames {
+    // `p::Command::new` -> `std::process::Command::new` when p renames std::process
+    fn resolve_aliases(&self, segments: &[String]) -> Vec<String> {
+        let mut resolved = segments.to_vec();
+
+        // an alias can point at another alias, a cycle only goes around as often as there are aliases
+        for _ in 0..=self.aliases.len() {
+            let Some(aliased_path) = resolved.first().and_then(|root| self.aliases.get(root))
+            else {
+                break;
+            };
+
+            resolved = aliased_path
+                .iter()
+                .chain(resolved.iter().skip(1))
+                .cloned()
+                .collect();
+        }
+
+        // a bare fs function is one of std::fs when the whole module is glob imported
+        let is_fs_glob_imported = self
+            .globs
+            .iter()
+            .any(|glob| glob.last().map(String::as_str) == Some("fs"));
+        if resolved.len() == 1
+            && is_fs_glob_imported
+            && STD_FS_FUNCTIONS.contains(&resolved[0].as_str())
+        {
+            resolved.splice(0..0, ["std".to_string(), "fs".to_string()]);
+        }
+
+        resolved
+    }
+
+    fn collect_imports(&mut self, use_tree: &UseTree, prefix: Vec<String>) {
+        let with_segment = |segment: String| {
+            let mut path = prefix.clone();
+            path.push(segment);
+            path
+        };
+
+        match use_tree {
+            UseTree::Path(use_path) => {
+                self.collect_imports(&use_path.tree, with_segment(use_path.ident.to_string()))
+            }
+            // `use std::fs::{self}` imports fs
+            UseTree::Name(use_name) if use_name.ident == "self" => {
+                if let Some(module_name) = prefix.last() {
+                    self.aliases.insert(module_name.clone(), prefix.clone());
+                }
+            }
+            UseTree::Name(use_name) => {
+                self.aliases.insert(
+                    use_name.ident.to_string(),
+                    with_segment(use_name.ident.to_string()),
+                );
+            }
+            UseTree::Rename(use_rename) => {
+                self.aliases.insert(
+                    use_rename.rename.to_string(),
+                    with_segment(use_rename.ident.to_string()),
+                );
+            }
+            UseTree::Glob(_) => self.globs.push(prefix),
+            UseTree::Group(use_group) => use_group
+                .items
+                .iter()
+                .for_each(|item| self.collect_imports(item, prefix.clone())),
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for LocalNames {
+    fn visit_item_mod(&mut self, item_mod: &'ast ItemMod) {
+        self.names.insert(item_mod.ident.to_string());
+        visit::visit_item_mod(self, item_mod);
+    }
+
+    fn visit_item_use(&mut self, item_use: &'ast ItemUse) {
+        let mut paths = Vec::new();
+        use_tree_paths(&item_use.tree, Vec::new(), &mut paths);
+        self.names
+            .extend(paths.iter().filter_map(|path| path.last().cloned()));
+
+        self.collect_imports(&item_use.tree, Vec::new());
+        self.names.extend(self.aliases.keys().cloned());
+    }
+
+    fn visit_item_const(&mut self, item_const: &'ast ItemConst) {
+        if let Some(value) = str_literal(&item_const.expr) {
+            self.str_consts.insert(item_const.ident.to_string(), value);
+        }
+    }
+
+    fn visit_item_static(&mut self, item_static: &'ast ItemStatic) {
+        if let Some(value) = str_literal(&item_static.expr) {
+            self.str_consts.insert(item_static.ident.to_string(), value);
+        }
+    }
+}
+
+fn str_literal(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Lit(expr_lit) => match &expr_lit.lit {
+            Lit::Str(lit_str) => Some(lit_str.value()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+struct PolicyVisitor<'a> {
+    policy: &'a CodePolicy,
+    local_names: LocalNames,
+    violations: Vec<PolicyViolation>,
+}
+
+impl PolicyVisitor<'_> {
+    fn check_crate_root(&mut self, root: &str, span: proc_macro2::Span) {
+        let is_known_root = BUILTIN_ROOTS.contains(&root)
+            || PRIMITIVE_TYPES.contains(&root)
+            || self.local_names.names.contains(root)
+            || self.policy.is_crate_allowed(root);
+
+        if !is_known_root {
+            self.violations.push(PolicyViolation::new(
+                span,
+                "crate_not_allowed",
+                format!(
+                    "Uses the crate `{}`, only {} are installed",
+                    root,
+                    self.policy.allowed_crates.join(", ")
+                ),
+            ));
+        }
+    }
+
+    fn check_process_spawn(&mut self, segments: &[String], span: proc_macro2::Span) {
+        if is_process_spawn(segments) {
+            self.violations.push(PolicyViolation::new(
+                span,
+                "process_spawn",
+                format!("Spawns processes through `{}`", segments.join("::")),
+            ));
+        }
+    }
+
+    // resolve a path argument to the literal it is built from, if any
+    fn resolve_path_arg(&self, expr: &Expr) -> Option<String> {
+        match expr {
+            Expr::Lit(_) => str_literal(expr),
+            Expr::Path(expr_path) => expr_path
+                .path
+                .get_ident()
+                .and_then(|ident| self.local_names.str_consts.get(&ident.to_string()))
+                .cloned(),
+            Expr::Reference(expr_reference) => self.resolve_path_arg(&expr_reference.expr),
+            Expr::Paren(expr_paren) => self.resolve_path_arg(&expr_paren.expr),
+            // Path::new("data/x") / PathBuf::from("data/x")
+            Expr::Call(expr_call) => expr_call
+                .args
+                .first()
+                .and_then(|arg| self.resolve_path_arg(arg)),
+            // "data/x".to_string()
+            Expr::MethodCall(expr_method_call) => self.resolve_path_arg(&expr_method_call.receiver),
+            _ => None,
+        }
+    }
+
+    fn check_fs_path(&mut self, operation: &str, path_arg: &Expr) {
+        match self.resolve_path_arg(path_arg) {
+            Some(path) if self.policy.is_path_in_data_dir(&path) => {}
+            Some(path) => self.violations.push(PolicyViolation::new(
+                path_arg.span(),
+                "filesystem_outside_data_dir",
+                format!(
+                    "`{}` uses \"{}\", files may only live under {}",
+                    operation, path, self.policy.allowed_data_dir
+                ),
+            )),
+            None => self.violations.push(PolicyViolation::new(
+                path_arg.span(),
+                "filesystem_outside_data_dir",
+                format!(
+                    "`{}` uses a path that cannot be checked, use a string literal or const under {}",
+                    operation, self.policy.allowed_data_dir
+                ),
+            )),
+        }
+    }
+
+    // json!({ .. }) and friends do not parse as expressions, fs and process paths in them are rejected
+    fn check_macro_tokens(&mut self, tokens: TokenStream) {
+        let mut paths = Vec::new();
+        macro_token_paths(tokens, &mut paths);
+
+        for (path, span) in paths {
+            let segments = self.local_names.resolve_aliases(&path);
+            self.check_process_spawn(&segments, span);
+
+            if is_fs_call(&segments) {
+                self.violations.push(PolicyViolation::new(
+                    span,
+                    "filesystem_outside_data_dir",
+                    format!(
+                        "`{}` is used inside a macro where its path cannot be checked, call it outside the macro",
+                        segments.join("::")
+                    ),
+                ));
+            }
+        }
+    }
+
+    fn push_unsafe(&mut self, span: proc_macro2::Span, item: &str) {
+        self.violations.push(PolicyViolation::new(
+            span,
+            "unsafe",
+            format!("Declares an unsafe {}", item),
+        ));
+    }
+}
+
+impl<'ast> Visit<'ast> for PolicyVisitor<'_> {
+    fn visit_item_use(&mut self, item_use: &'ast ItemUse) {
+        let mut paths = Vec::new();
+        use_tree_paths(&item_use.tree, Vec::new(), &mut paths);
+
+        for path in paths {
+            if let Some(root) = path.first() {
+                self.check_crate_root(root, item_use.span());
+            }
+            self.check_process_spawn(&self.local_names.resolve_aliases(&path), item_use.span());
+        }
+    }
+
+    fn visit_item_extern_crate(&mut self, item_extern_crate: &'ast ItemExternCrate) {
+        self.check_crate_root(
+            &item_extern_crate.ident.to_string(),
+            item_extern_crate.span(),
+        );
+    }
+
+    fn visit_path(&mut self, path: &'ast Path) {
+        let segments = path_segments(path);
+
+        // single idents are locals, types or functions in scope
+        let is_module_path = segments.len() > 1 || path.leading_colon.is_some();
+        let is_type_path = segments
+            .first()
+            .map(|root| root.starts_with(char::is_uppercase))
+            .unwrap_or(true);
+
+        if is_module_path && !is_type_path {
+            self.check_crate_root(&segments[0], path.span());
+        }
+        self.check_process_spawn(&self.local_names.resolve_aliases(&segments), path.span());
+
+        visit::visit_path(self, path);
+    }
+
+    fn visit_expr_call(&mut self, expr_call: &'ast ExprCall) {
+        if let Expr::Path(expr_path) = expr_call.func.as_ref() {
+            let segments = self
+                .local_names
+                .resolve_aliases(&path_segments(&expr_path.path));
+            let operation = segments.join("::");
+            let function_name = segments.last().cloned().unwrap_or_default();
+
+            if is_fs_call(&segments) {
+                let path_arg_count = if TWO_PATH_FS_FUNCTIONS.contains(&function_name.as_str()) {
+                    2
+                } else {
+                    1
+                };
+
+                for path_arg in expr_call.args.iter().take(path_arg_count) {
+                    self.check_fs_path(&operation, path_arg);
+                }
+            }
+        }
+
+        visit::visit_expr_call(self, expr_call);
+    }
+
+    fn visit_expr_method_call(&mut self, expr_method_call: &'ast ExprMethodCall) {
+        // OpenOptions::new().write(true).open(path)
+        let mut receiver = expr_method_call.receiver.as_ref();
+        let is_open_options = loop {
+            match receiver {
+                Expr::MethodCall(inner_call) => receiver = inner_call.receiver.as_ref(),
+                Expr::Call(call) => {
+                    break matches!(call.func.as_ref(), Expr::Path(expr_path)
+                        if path_segments(&expr_path.path).contains(&"OpenOptions".to_string()))
+                }
+                _ => break false,
+            }
+        };
+
+        if is_open_options && expr_method_call.method == "open" {
+            if let Some(path_arg) = expr_method_call.args.first() {
+                self.check_fs_path("OpenOptions::open", path_arg);
+            }
+        }
+
+        visit::visit_expr_method_call(self, expr_method_call);
+    }
+
+    // vec![..], format!(..) take expressions, the calls in them get the same checks
+    fn visit_macro(&mut self, mac: &'ast Macro) {
+        match mac.parse_body_with(Punctuated::<Expr, Token![,]>::parse_terminated) {
+            Ok(exprs) => exprs.iter().for_each(|expr| self.visit_expr(expr)),
+            Err(_) => self.check_macro_tokens(mac.tokens.clone()),
+        }
+
+        visit::visit_macro(self, mac);
+    }
+
+    fn visit_expr_unsafe(&mut self, expr_unsafe: &'ast ExprUnsafe) {
+        self.push_unsafe(expr_unsafe.span(), "block");
+        visit::visit_expr_unsafe(self, expr_unsafe);
+    }
+
+    fn visit_item_fn(&mut self, item_fn: &'ast ItemFn) {
+        if item_fn.sig.unsafety.is_some() {
+            self.push_unsafe(item_fn.sig.span(), "function");
+        }
+        visit::visit_item_fn(self, item_fn);
+    }
+
+    fn visit_impl_item_fn(&mut self, impl_item_fn: &'ast ImplItemFn) {
+        if impl_item_fn.sig.unsafety.is_some() {
+            self.push_unsafe(impl_item_fn.sig.span(), "function");
+        }
+        visit::visit_impl_item_fn(self, impl_item_fn);
+    }
+
+    fn visit_item_impl(&mut self, item_impl: &'ast ItemImpl) {
+        if let Some(unsafety) = item_impl.unsafety {
+            self.push_unsafe(unsafety.span(), "impl");
+        }
+        visit::visit_item_impl(self, item_impl);
+    }
+
+    fn visit_item_trait(&mut self, item_trait: &'ast ItemTrait) {
+        if let Some(unsafety) = item_trait.unsafety {
+            self.push_unsafe(unsafety.span(), "trait");
+        }
+        visit::visit_item_trait(self, item_trait);
+    }
+
+    fn visit_item_foreign_mod(&mut self, item_foreign_mod: &'ast ItemForeignMod) {
+        self.push_unsafe(item_foreign_mod.span(), "extern block");
+    }
+}
+
+// parse the generated code and list everything that breaks the policy
+pub fn check_code_policy(code: &str, policy: &CodePolicy) -> Vec<PolicyViolation> {
+    let syntax_tree = match syn::parse_file(code) {
+        Ok(syntax_tree) => syntax_tree,
+        Err(e) => {
+            return vec![PolicyViolation::new(
+                e.span(),
+                "parse_error",
+                format!("The code is not valid Rust: {}", e),
+            )]
+        }
+    };
+
+    let mut local_names = LocalNames::default();
+    local_names.visit_file(&syntax_tree);
+
+    let mut policy_visitor = PolicyVisitor {
+        policy,
+        local_names,
+        violations: Vec::new(),
+    };
+    policy_visitor.visit_file(&syntax_tree);
+
+    policy_visitor.violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(code: &str) -> Vec<(String, usize)> {
+        check_code_policy(code, &CodePolicy::backend())
+            .into_iter()
+            .map(|violation| (violation.rule, violation.line))
+            .collect()
+    }
+
+    #[test]
+    fn test_allowed_code_passes() {
+        let code = r#"
+use actix_web::{web, App, HttpResponse, HttpServer};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::sync::Mutex;
+
+mod db;
+
+const DATABASE_PATH: &str = "./data/database.json";
+
+#[derive(Serialize, Deserialize)]
+struct Item { id: u64 }
+
+async fn items(data: web::Data<Mutex<Vec<Item>>>) -> HttpResponse {
+    let raw = fs::read_to_string(DATABASE_PATH).unwrap_or_default();
+    fs::write("data/backup.json", raw).unwrap();
+    let max = u64::MAX;
+    db::load();
+    HttpResponse::Ok().json(serde_json::json!({ "max": max }))
+}
+
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    HttpServer::new(|| App::new().route("/items", web::get().to(items)))
+        .bind(("127.0.0.1", 8080))?
+        .run()
+        .await
+}
+"#;
+
+        assert_eq!(rules(code), vec![]);
+    }
+
+    #[test]
+    fn test_violations_are_found() {
+        let code = r#"
+use rand::Rng;
+use std::process::Command;
+
+fn run(path: &str) {
+    Command::new("ls").spawn().unwrap();
+    std::fs::remove_file("/etc/passwd").unwrap();
+    std::fs::write(path, "x").unwrap();
+    let raw = unsafe { std::str::from_utf8_unchecked(b"x") };
+    let value = chrono::Utc::now();
+    std::fs::OpenOptions::new().write(true).open("../outside.txt").unwrap();
+}
+"#;
+
+        assert_eq!(
+            rules(code),
+            vec![
+                ("crate_not_allowed".to_string(), 2),
+                ("process_spawn".to_string(), 3),
+                ("process_spawn".to_string(), 6),
+                ("filesystem_outside_data_dir".to_string(), 7),
+                ("filesystem_outside_data_dir".to_string(), 8),
+                ("unsafe".to_string(), 9),
+                ("crate_not_allowed".to_string(), 10),
+                ("filesystem_outside_data_dir".to_string(), 11),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_renamed_imports_are_resolved() {
+        let code = r#"
+use std::process as p;
+use std::process::Command as C;
+use std::fs as f;
+
+fn run() {
+    p::Command::new("ls").spawn().unwrap();
+    C::new("ls").spawn().unwrap();
+    f::remove_file("/etc/passwd").unwrap();
+}
+"#;
+
+        assert_eq!(
+            rules(code),
+            vec![
+                ("process_spawn".to_string(), 3),
+                ("process_spawn".to_string(), 7),
+                ("process_spawn".to_string(), 8),
+                ("filesystem_outside_data_dir".to_string(), 9),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_imported_functions_are_resolved() {
+        let code = r#"
+use std::fs::write;
+use std::fs::{self, remove_dir_all};
+use std::fs::*;
+
+fn run() {
+    write("/etc/passwd", "x").unwrap();
+    remove_dir_all("/").unwrap();
+    create_dir_all("data/items").unwrap();
+    remove_file("/etc/hosts").unwrap();
+    fs::write("data/items.json", "[]").unwrap();
+}
+"#;
+
+        assert_eq!(
+            rules(code),
+            vec![
+                ("filesystem_outside_data_dir".to_string(), 7),
+                ("filesystem_outside_data_dir".to_string(), 8),
+                ("filesystem_outside_data_dir".to_string(), 10),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_calls_in_macros_are_checked() {
+        let code = r#"
+use std::process::Command as C;
+
+fn run() {
+    let removed = vec![std::fs::remove_dir_all("/")];
+    let message = format!("{:?}", std::fs::read_to_string("data/items.json"));
+    let body = serde_json::json!({ "hosts": std::fs::read_to_string("data/hosts") });
+    println!("{:?}", serde_json::json!({ "ls": C::new("ls").output() }));
+}
+"#;
+
+        assert_eq!(
+            rules(code),
+            vec![
+                ("process_spawn".to_string(), 2),
+                ("filesystem_outside_data_dir".to_string(), 5),
+                ("filesystem_outside_data_dir".to_string(), 7),
+                ("process_spawn".to_string(), 8),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_broken_code_is_reported() {
+        let violations = check_code_policy("fn main( {", &CodePolicy::backend());
+
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule, "parse_error");
+        assert!(format_policy_violations(&violations).starts_with("POLICY_VIOLATION (parse_error)"));
+    }
+
+    #[test]
+    fn test_policy_for_factsheet() {
+        let factsheet: FactSheet = serde_json::from_str(
+            r#"{
+              "project_description": null,
+              "project_scope": {
+                "is_crud_required": true,
+                "is_user_login_and_logout": true,
+                "is_external_urls_required": false
+              },
+              "external_urls": null,
+              "database_code": "pub fn open_database() {}",
+              "backend_code": null,
+              "api_enpoint_scheme": null
+            }"#,
+        )
+        .unwrap();
+
+        let policy = CodePolicy::for_factsheet(&factsheet);
+
+        assert!(policy.is_crate_allowed("rusqlite"));
+        assert!(policy.is_crate_allowed("argon2"));
+        assert!(policy.is_crate_allowed("actix_web"));
+        assert!(!CodePolicy::backend().is_crate_allowed("rusqlite"));
+    }
+}
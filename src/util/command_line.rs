@@ -2,47 +2,68 @@ use crossterm::{
     style::{Color, ResetColor, SetForegroundColor},
     ExecutableCommand,
 };
+use serde::Serialize;
 use std::io::{stdin, stdout};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
 
-#[derive(PartialEq, Debug)]
+use super::event_bus::{publish, AgentEvent};
+
+// set for unattended runs (eg. `hoppity eval`) where nobody can answer the prompt
+static AUTO_APPROVE: AtomicBool = AtomicBool::new(false);
+
+// agents running side by side ask one after the other
+static APPROVAL_PROMPT: Mutex<()> = Mutex::new(());
+
+pub fn set_auto_approve(is_auto_approve: bool) {
+    AUTO_APPROVE.store(is_auto_approve, Ordering::SeqCst);
+}
+
+#[derive(PartialEq, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum PrintCommand {
+    #[serde(rename = "api_call")]
     APICall,
     Issue,
     UnitTest,
 }
 
 // display ai function commentary
-// this is let us know what is being currently done by our ai functions
+// this is let us know what is being currently done by our ai functions, the subscribers of the event bus show it
 impl PrintCommand {
     pub fn print_agent_action(&self, agent_position: &str, agent_statement: &str) {
-        let mut stdout: std::io::Stdout = stdout();
-
-        let current_action_color = match self {
-            PrintCommand::APICall => Color::DarkBlue,
-            PrintCommand::UnitTest => Color::Green,
-            PrintCommand::Issue => Color::Red,
-        };
+        publish(AgentEvent::Action {
+            agent: agent_position.to_string(),
+            kind: *self,
+            message: agent_statement.to_string(),
+        });
+    }
+}
 
-        // print the agent's current position in terminal
-        stdout.execute(SetForegroundColor(Color::Magenta)).unwrap();
+pub fn get_user_approval() -> bool {
+    let is_approved = ask_user_approval();
 
-        print!("#AGENT -> {} ", agent_position);
+    publish(AgentEvent::UserDecision {
+        is_approved,
+        is_auto_approved: is_approved && AUTO_APPROVE.load(Ordering::SeqCst),
+    });
 
-        // print agent's comment or statement in color based on it's action
-        stdout
-            .execute(SetForegroundColor(current_action_color))
-            .unwrap();
+    is_approved
+}
 
-        print!("Stage --> {} ", agent_statement);
+fn ask_user_approval() -> bool {
+    let mut stdout = stdout();
 
-        // reset the color in our terminal
+    if AUTO_APPROVE.load(Ordering::SeqCst) {
+        stdout.execute(SetForegroundColor(Color::Yellow)).unwrap();
+        println!("Auto approval enabled, proceeding without user confirmation");
         stdout.execute(ResetColor).unwrap();
-        println!("");
+        return true;
     }
-}
 
-pub fn get_user_approval() -> bool {
-    let mut stdout = stdout();
+    let _approval_prompt = APPROVAL_PROMPT
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
 
     loop {
         stdout.execute(SetForegroundColor(Color::Yellow)).unwrap();
@@ -1,10 +1,19 @@
 use std::fs;
+use std::path::Path;
 
 use reqwest::Client;
 use serde::de::DeserializeOwned;
 use serde_json;
+use tracing::{field, instrument};
 
 use super::command_line::PrintCommand;
+use super::event_bus::{publish, AgentEvent};
+use crate::ai_function::aifunc_memory::print_memory_summary;
+use crate::model::basic_agents::agent_memory::{
+    plan_memory_compaction, MemoryStrategy, MEMORY_SUMMARY_PREFIX,
+};
+use crate::model::basic_agents::basic_agent_traits::BasicAgentTrait;
+use crate::model::basic_agents::basic_agents::BasicAgent;
 use crate::{model::common::large_language_model::Message, service::call_open_api::call_gpt};
 
 const CODE_TEMPLATE_PATH: &str =
@@ -13,8 +22,27 @@ const CODE_TEMPLATE_PATH: &str =
 const EXECUTED_MAIN_PATH: &str = "/home/bunny/my_stuff/projects/rust/hoppity-bin/src/main.rs";
 pub const EXECUTING_PROJECT_ROOT_PATH: &str = "/home/bunny/my_stuff/projects/rust/hoppity-bin/";
 
+const GENERATED_TESTS_PATH: &str =
+    "/home/bunny/my_stuff/projects/rust/hoppity-bin/src/generated_tests.rs";
+
+const FRONTEND_INDEX_PATH: &str =
+    "/home/bunny/my_stuff/projects/rust/hoppity-bin/frontend/index.html";
+
+const DATABASE_MIGRATIONS_PATH: &str =
+    "/home/bunny/my_stuff/projects/rust/hoppity-bin/migrations/0001_init.sql";
+const DATABASE_CODE_PATH: &str = "/home/bunny/my_stuff/projects/rust/hoppity-bin/src/db.rs";
+pub const DATABASE_CHECK_PATH: &str =
+    "/home/bunny/my_stuff/projects/rust/hoppity-bin/data/migration_check.db";
+
 const API_SCHEMA_PATH: &str =
     "/home/bunny/my_stuff/projects/rust/rust-hoppity/schemas/api_schema.json";
+const OPENAPI_PATH: &str = "/home/bunny/my_stuff/projects/rust/hoppity-bin/openapi.json";
+pub const CLIENT_SDK_ROOT_PATH: &str = "/home/bunny/my_stuff/projects/rust/hoppity-bin/client/";
+const POSTMAN_COLLECTION_PATH: &str =
+    "/home/bunny/my_stuff/projects/rust/hoppity-bin/postman_collection.json";
+const HTTP_FILE_PATH: &str = "/home/bunny/my_stuff/projects/rust/hoppity-bin/api.http";
+const RUN_REPORT_PATH: &str =
+    "/home/bunny/my_stuff/projects/rust/rust-hoppity/schemas/run_report.json";
 
 // read code template
 pub fn read_code_template() -> String {
@@ -31,12 +59,106 @@ pub fn read_backend_code() -> String {
     fs::read_to_string(String::from(EXECUTED_MAIN_PATH)).expect("Failed to read Backend Code")
 }
 
+// write a file of the generated project, creating its directory if needed
+fn write_project_file(path: &str, contents: &str) -> std::io::Result<()> {
+    if let Some(parent_dir) = Path::new(path).parent() {
+        fs::create_dir_all(parent_dir)?;
+    }
+
+    fs::write(path, contents)
+}
+
+// save the generated integration tests (generated_tests.rs next to main.rs)
+pub fn save_generated_tests(contents: &str) {
+    write_project_file(GENERATED_TESTS_PATH, contents)
+        .expect("Failed to write in generated_tests.rs file");
+}
+
+// save new frontend code
+pub fn save_frontend_code(contents: &str) {
+    write_project_file(FRONTEND_INDEX_PATH, contents).expect("Failed to write in index.html file");
+}
+
+// save database migrations
+pub fn save_database_migrations(contents: &str) {
+    write_project_file(DATABASE_MIGRATIONS_PATH, contents)
+        .expect("Failed to write the database migrations");
+}
+
+// save database access code (db.rs next to main.rs)
+pub fn save_database_code(contents: &str) {
+    write_project_file(DATABASE_CODE_PATH, contents).expect("Failed to write in db.rs file");
+}
+
 // save JSON api endpoints
 pub fn save_endpoints(api_endpoint_schema: &String) {
     fs::write(String::from(API_SCHEMA_PATH), api_endpoint_schema)
         .expect("Failed to save API Endpoint Schema to JSON file");
 }
 
+// save the openapi document next to the generated project
+pub fn save_openapi_document(openapi_document: &str) {
+    fs::write(OPENAPI_PATH, openapi_document)
+        .expect("Failed to save the OpenAPI document to JSON file");
+}
+
+// save the postman collection next to the generated project
+pub fn save_postman_collection(postman_collection: &str) {
+    fs::write(POSTMAN_COLLECTION_PATH, postman_collection)
+        .expect("Failed to save the Postman collection to JSON file");
+}
+
+// save the REST Client requests next to the generated project
+pub fn save_http_file(http_file: &str) {
+    fs::write(HTTP_FILE_PATH, http_file).expect("Failed to save the api.http file");
+}
+
+// the generated project's Cargo.toml, the client crate builds on the same dependency versions
+pub fn read_backend_cargo_toml() -> String {
+    fs::read_to_string(format!("{}Cargo.toml", EXECUTING_PROJECT_ROOT_PATH)).unwrap_or_default()
+}
+
+// the version a [dependencies] line of Cargo.toml asks for, eg. reqwest = { version = "0.11.22" }
+pub fn dependency_version(cargo_toml: &str, crate_name: &str) -> Option<String> {
+    cargo_toml.lines().find_map(|line| {
+        let value = line
+            .trim()
+            .strip_prefix(crate_name)?
+            .trim_start()
+            .strip_prefix('=')?
+            .trim();
+
+        let version_part = match value.find("version") {
+            Some(version_index) => &value[version_index..],
+            None => value,
+        };
+
+        version_part
+            .split('"')
+            .nth(1)
+            .map(|version| version.to_string())
+    })
+}
+
+// save the client crate (client/ inside the generated project), with the project's lock file
+pub fn save_client_sdk(cargo_toml: &str, lib_rs: &str) {
+    write_project_file(&format!("{}Cargo.toml", CLIENT_SDK_ROOT_PATH), cargo_toml)
+        .expect("Failed to write the client Cargo.toml");
+    write_project_file(&format!("{}src/lib.rs", CLIENT_SDK_ROOT_PATH), lib_rs)
+        .expect("Failed to write the client lib.rs");
+
+    // pins the client to the versions that are already downloaded
+    let _ = fs::copy(
+        format!("{}Cargo.lock", EXECUTING_PROJECT_ROOT_PATH),
+        format!("{}Cargo.lock", CLIENT_SDK_ROOT_PATH),
+    );
+}
+
+// save the endpoint validation report of the last run, failed endpoints carry the server logs
+pub fn save_run_report(run_report: &str) {
+    fs::write(RUN_REPORT_PATH, run_report).expect("Failed to save the run report to JSON file");
+}
+
 // Extend our ai function to print in specific type (Message)
 pub fn extend_ai_func(ai_func: fn(&str) -> &str, fn_arg: &str) -> Message {
     let ai_func_str = ai_func(fn_arg);
@@ -55,6 +177,51 @@ pub fn extend_ai_func(ai_func: fn(&str) -> &str, fn_arg: &str) -> Message {
     }
 }
 
+async fn request_llm(messages: Vec<Message>, agent_pos: &str, agent_operation: &str) -> String {
+    // print ai function progress
+    PrintCommand::APICall.print_agent_action(agent_pos, agent_operation);
+
+    // call large language model, once more if the first call fails
+    match call_llm(messages.clone(), agent_pos, agent_operation).await {
+        Ok(response) => response,
+        Err(_) => call_llm(messages, agent_pos, agent_operation)
+            .await
+            .expect("Fetching from OpenAI Failed"),
+    }
+}
+
+#[instrument(
+    name = "llm_call",
+    skip_all,
+    fields(
+        agent.position = agent_pos,
+        ai_function = agent_operation,
+        llm.prompt_tokens = field::Empty,
+        llm.completion_tokens = field::Empty,
+        llm.total_tokens = field::Empty,
+    )
+)]
+async fn call_llm(
+    messages: Vec<Message>,
+    agent_pos: &str,
+    agent_operation: &str,
+) -> Result<String, Box<dyn std::error::Error + Send>> {
+    publish(AgentEvent::LlmCallStarted {
+        agent: agent_pos.to_string(),
+        operation: agent_operation.to_string(),
+    });
+
+    let llm_response = call_gpt(messages).await;
+
+    publish(AgentEvent::LlmCallFinished {
+        agent: agent_pos.to_string(),
+        operation: agent_operation.to_string(),
+        is_success: llm_response.is_ok(),
+    });
+
+    llm_response
+}
+
 pub async fn ai_task_request(
     ai_func: for<'a> fn(&'a str) -> &'static str,
     msg_context: &str,
@@ -64,29 +231,107 @@ pub async fn ai_task_request(
     // extend ai function to get Message
     let extended_message = extend_ai_func(ai_func, msg_context);
 
-    // print ai function progress
-    PrintCommand::APICall.print_agent_action(agent_pos, agent_operation);
+    request_llm(vec![extended_message], agent_pos, agent_operation).await
+}
 
-    // call large language model
-    match call_gpt(vec![extended_message.clone()]).await {
-        Ok(response) => response,
-        Err(_) => call_gpt(vec![extended_message])
-            .await
-            .expect("Fetching from OpenAI Failed"),
+// keep the agent's memory within its context budget using the agent's memory strategy
+async fn compact_memory(agent: &mut BasicAgent) {
+    let Some(compaction) = plan_memory_compaction(&agent.memory, &agent.memory_strategy) else {
+        return;
+    };
+
+    let mut compacted_memory: Vec<Message> = Vec::new();
+
+    if let MemoryStrategy::Summarize { .. } = agent.memory_strategy {
+        let conversation_history = compaction
+            .compressed
+            .iter()
+            .map(|message| format!("{}: {}", message.role.to_uppercase(), message.content))
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        let summary = ai_task_request(
+            print_memory_summary,
+            &format!("CONVERSATION_HISTORY: {}", conversation_history),
+            &agent.position,
+            stringify!(print_memory_summary),
+        )
+        .await;
+
+        compacted_memory.push(Message {
+            role: "system".to_string(),
+            content: format!("{} {}", MEMORY_SUMMARY_PREFIX, summary),
+        });
     }
+
+    compacted_memory.extend(compaction.kept);
+    agent.memory = compacted_memory;
 }
 
-pub async fn ai_task_request_decoded<T: DeserializeOwned>(
+// same as ai_task_request, but the exchange is stored in the agent's memory
+// is_memory_included sends the previous exchanges along as conversation history
+pub async fn ai_task_request_with_memory(
     ai_func: for<'a> fn(&'a str) -> &'static str,
     msg_context: &str,
-    agent_pos: &str,
+    agent: &mut BasicAgent,
     agent_operation: &str,
-) -> T {
-    let ai_task_request = ai_task_request(ai_func, msg_context, agent_pos, agent_operation).await;
+    is_memory_included: bool,
+) -> String {
+    let extended_message = extend_ai_func(ai_func, msg_context);
+
+    if is_memory_included {
+        compact_memory(agent).await;
+    }
+    let messages = build_task_messages(agent, &extended_message, is_memory_included);
+
+    let ai_response = request_llm(messages, &agent.position, agent_operation).await;
 
-    let decoded_result = serde_json::from_str(&ai_task_request).expect("Failed to decode response");
+    record_exchange(agent, extended_message, &ai_response);
 
-    decoded_result
+    ai_response
+}
+
+// the previous exchanges come first, the new task is always the last message
+fn build_task_messages(
+    agent: &BasicAgent,
+    extended_message: &Message,
+    is_memory_included: bool,
+) -> Vec<Message> {
+    let mut messages: Vec<Message> = if is_memory_included {
+        agent.get_memory().clone()
+    } else {
+        Vec::new()
+    };
+    messages.push(extended_message.clone());
+
+    messages
+}
+
+fn record_exchange(agent: &mut BasicAgent, extended_message: Message, ai_response: &str) {
+    agent.add_memory(extended_message);
+    agent.add_memory(Message {
+        role: "assistant".to_string(),
+        content: ai_response.to_string(),
+    });
+}
+
+pub async fn ai_task_request_decoded_with_memory<T: DeserializeOwned>(
+    ai_func: for<'a> fn(&'a str) -> &'static str,
+    msg_context: &str,
+    agent: &mut BasicAgent,
+    agent_operation: &str,
+    is_memory_included: bool,
+) -> T {
+    let ai_task_request = ai_task_request_with_memory(
+        ai_func,
+        msg_context,
+        agent,
+        agent_operation,
+        is_memory_included,
+    )
+    .await;
+
+    serde_json::from_str(&ai_task_request).expect("Failed to decode response")
 }
 
 // check if the provided url is valid
@@ -102,6 +347,60 @@ mod tests {
         aifunc_architect::print_project_scope, aifunc_managing::convert_user_input_to_goal,
     };
 
+    #[test]
+    fn test_dependency_version() {
+        let cargo_toml = "[dependencies]\nrusqlite = { version = \"0.29.0\", features = [\"bundled\"] }\nserde_json = \"1.0.107\"\n";
+
+        assert_eq!(
+            dependency_version(cargo_toml, "rusqlite"),
+            Some("0.29.0".to_string())
+        );
+        assert_eq!(
+            dependency_version(cargo_toml, "serde_json"),
+            Some("1.0.107".to_string())
+        );
+        assert_eq!(dependency_version(cargo_toml, "serde"), None);
+    }
+
+    #[test]
+    fn test_memory_is_replayed_in_order() {
+        let mut agent = BasicAgent::new("build a website".to_string(), "Backend".to_string());
+
+        // the first task is sent on its own and recorded with its answer
+        let write_message = extend_ai_func(print_project_scope, "a todo app");
+        assert_eq!(
+            build_task_messages(&agent, &write_message, true),
+            vec![write_message.clone()]
+        );
+        record_exchange(&mut agent, write_message.clone(), "fn main() {}");
+
+        // fixing the code replays the earlier exchange before the new task
+        let fix_message = extend_ai_func(convert_user_input_to_goal, "fix the build");
+        let messages = build_task_messages(&agent, &fix_message, true);
+        let contents: Vec<(&str, &str)> = messages
+            .iter()
+            .map(|message| (message.role.as_str(), message.content.as_str()))
+            .collect();
+        assert_eq!(
+            contents,
+            vec![
+                ("system", write_message.content.as_str()),
+                ("assistant", "fn main() {}"),
+                ("system", fix_message.content.as_str()),
+            ]
+        );
+
+        // without memory only the new task is sent, the exchange is still recorded
+        assert_eq!(
+            build_task_messages(&agent, &fix_message, false),
+            vec![fix_message.clone()]
+        );
+        record_exchange(&mut agent, fix_message.clone(), "fn main() { run() }");
+        assert_eq!(agent.get_memory().len(), 4);
+        assert_eq!(agent.get_memory()[2], fix_message);
+        assert_eq!(agent.get_memory()[3].content, "fn main() { run() }");
+    }
+
     #[test]
     fn test_extend_ai_func() {
         let extended_msg = extend_ai_func(print_project_scope, "dummy thing!!");
@@ -0,0 +1,267 @@
+use tokio::io::{copy_bidirectional, AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+const MAX_REQUEST_HEAD_BYTES: usize = 16 * 1024;
+
+// host of an approved external url, eg. "https://api.binance.com/api/v3/ticker" -> "api.binance.com"
+pub fn url_host(url: &str) -> Option<String> {
+    let without_scheme = url.split("://").nth(1).unwrap_or(url);
+    let authority = without_scheme.split(['/', '?', '#']).next()?;
+    let host = authority.rsplit('@').next()?;
+
+    // "[::1]:8080" -> "::1", "host:8080" -> "host"
+    let host = match host.strip_prefix('[') {
+        Some(ipv6_host) => ipv6_host.split(']').next()?,
+        None => host.split(':').next()?,
+    };
+
+    (!host.is_empty()).then(|| host.to_lowercase())
+}
+
+// where the sandboxed server may connect, https is tunneled with CONNECT, http is forwarded as is
+#[derive(Debug, Clone, PartialEq)]
+pub struct EgressTarget {
+    pub host: String,
+    pub port: u16,
+    pub is_tunnel: bool,
+}
+
+// explicit port of an authority, eg. "host:8080" -> 8080, "[::1]:8080" -> 8080, "host" -> None
+fn authority_port(authority: &str) -> Option<Result<u16, ()>> {
+    let host_port = authority.rsplit('@').next()?;
+    let port = match host_port.strip_prefix('[') {
+        Some(ipv6_host_port) => ipv6_host_port.split_once("]:")?.1,
+        None => host_port.rsplit_once(':')?.1,
+    };
+
+    Some(port.parse().map_err(|_| ()))
+}
+
+// only the scheme and port of an approved url are reachable, not every port of its host
+// eg. "https://api.binance.com/api/v3/ticker" -> api.binance.com:443 tunneled
+pub fn url_target(url: &str) -> Option<EgressTarget> {
+    let (scheme, without_scheme) = url.split_once("://")?;
+    let (default_port, is_tunnel) = match scheme.to_lowercase().as_str() {
+        "https" => (443, true),
+        "http" => (80, false),
+        _ => return None,
+    };
+
+    let authority = without_scheme.split(['/', '?', '#']).next()?;
+    let port = match authority_port(authority) {
+        Some(port) => port.ok()?,
+        None => default_port,
+    };
+
+    Some(EgressTarget {
+        host: url_host(url)?,
+        port,
+        is_tunnel,
+    })
+}
+
+pub fn is_target_allowed(target: &EgressTarget, allowed_targets: &[EgressTarget]) -> bool {
+    allowed_targets.contains(target)
+}
+
+// "CONNECT api.binance.com:443 HTTP/1.1" or "GET http://api.binance.com/x HTTP/1.1" -> target
+fn request_target(request_line: &str) -> Option<EgressTarget> {
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next()?;
+    let target = parts.next()?;
+
+    if method.eq_ignore_ascii_case("CONNECT") {
+        let (host, port) = target.rsplit_once(':')?;
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        return Some(EgressTarget {
+            host: host.to_lowercase(),
+            port: port.parse().ok()?,
+            is_tunnel: true,
+        });
+    }
+
+    // a plain request can only be http, an https url in it would be sent unencrypted
+    url_target(target).filter(|target| !target.is_tunnel)
+}
+
+async fn read_request_head(stream: &mut TcpStream) -> Option<Vec<u8>> {
+    let mut request_head: Vec<u8> = Vec::new();
+    let mut read_buffer = [0u8; 1024];
+
+    while !request_head.windows(4).any(|window| window == b"\r\n\r\n") {
+        let read_bytes = stream.read(&mut read_buffer).await.ok()?;
+        if read_bytes == 0 || request_head.len() > MAX_REQUEST_HEAD_BYTES {
+            return None;
+        }
+        request_head.extend_from_slice(&read_buffer[..read_bytes]);
+    }
+
+    Some(request_head)
+}
+
+async fn handle_proxy_connection(mut client_stream: TcpStream, allowed_targets: Vec<EgressTarget>) {
+    let Some(request_head) = read_request_head(&mut client_stream).await else {
+        return;
+    };
+
+    let request_line = String::from_utf8_lossy(&request_head)
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .to_string();
+
+    let Some(target) = request_target(&request_line) else {
+        let _ = client_stream
+            .write_all(b"HTTP/1.1 400 Bad Request\r\nConnection: close\r\n\r\n")
+            .await;
+        return;
+    };
+
+    if !is_target_allowed(&target, &allowed_targets) {
+        let _ = client_stream
+            .write_all(b"HTTP/1.1 403 Forbidden\r\nConnection: close\r\n\r\ntarget is not an approved external url")
+            .await;
+        return;
+    }
+
+    let Ok(mut upstream_stream) = TcpStream::connect((target.host.as_str(), target.port)).await
+    else {
+        let _ = client_stream
+            .write_all(b"HTTP/1.1 502 Bad Gateway\r\nConnection: close\r\n\r\n")
+            .await;
+        return;
+    };
+
+    // https is tunneled, plain http requests are forwarded as they came in
+    let forward_result = if target.is_tunnel {
+        client_stream
+            .write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n")
+            .await
+    } else {
+        upstream_stream.write_all(&request_head).await
+    };
+
+    if forward_result.is_ok() {
+        let _ = copy_bidirectional(&mut client_stream, &mut upstream_stream).await;
+    }
+}
+
+// http(s) proxy on a free local port that only lets the sandboxed server reach the approved urls
+// returns the port and the proxy task, abort the task to stop it
+// only tcp goes through it, landlock does not restrict udp so dns lookups still reach the resolver
+pub async fn start_egress_proxy(
+    allowed_targets: Vec<EgressTarget>,
+) -> std::io::Result<(u16, JoinHandle<()>)> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let port = listener.local_addr()?.port();
+
+    let proxy_task = tokio::spawn(async move {
+        while let Ok((client_stream, _)) = listener.accept().await {
+            tokio::spawn(handle_proxy_connection(
+                client_stream,
+                allowed_targets.clone(),
+            ));
+        }
+    });
+
+    Ok((port, proxy_task))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_host() {
+        assert_eq!(
+            url_host("https://api.Binance.com:443/api/v3/ticker?symbol=BTC"),
+            Some("api.binance.com".to_string())
+        );
+        assert_eq!(url_host("http://[::1]:8080/"), Some("::1".to_string()));
+        assert_eq!(url_host("https:///path"), None);
+    }
+
+    fn target(host: &str, port: u16, is_tunnel: bool) -> EgressTarget {
+        EgressTarget {
+            host: host.to_string(),
+            port,
+            is_tunnel,
+        }
+    }
+
+    #[test]
+    fn test_url_target() {
+        assert_eq!(
+            url_target("https://api.Binance.com/api/v3/ticker"),
+            Some(target("api.binance.com", 443, true))
+        );
+        assert_eq!(
+            url_target("http://user@[::1]:8080/"),
+            Some(target("::1", 8080, false))
+        );
+        assert_eq!(url_target("api.binance.com/api/v3/ticker"), None);
+        assert_eq!(url_target("ftp://example.com/file"), None);
+        assert_eq!(url_target("http://example.com:port/"), None);
+    }
+
+    #[test]
+    fn test_request_target() {
+        assert_eq!(
+            request_target("CONNECT api.binance.com:443 HTTP/1.1"),
+            Some(target("api.binance.com", 443, true))
+        );
+        assert_eq!(
+            request_target("GET http://example.com:8000/a HTTP/1.1"),
+            Some(target("example.com", 8000, false))
+        );
+        assert_eq!(request_target("GET https://example.com/a HTTP/1.1"), None);
+        assert_eq!(request_target("GET /a HTTP/1.1"), None);
+    }
+
+    async fn proxy_response(port: u16, request: &[u8]) -> String {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        stream.write_all(request).await.unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_egress_proxy_rejects_unknown_hosts() {
+        let allowed_targets = vec![url_target("https://api.binance.com/api/v3/ticker").unwrap()];
+        let (port, proxy_task) = start_egress_proxy(allowed_targets).await.unwrap();
+
+        let response = proxy_response(
+            port,
+            b"CONNECT evil.example.com:443 HTTP/1.1\r\nHost: evil.example.com\r\n\r\n",
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 403"));
+
+        proxy_task.abort();
+    }
+
+    #[tokio::test]
+    async fn test_egress_proxy_rejects_other_ports_and_schemes() {
+        let allowed_targets = vec![url_target("https://api.binance.com/api/v3/ticker").unwrap()];
+        let (port, proxy_task) = start_egress_proxy(allowed_targets).await.unwrap();
+
+        let other_port = proxy_response(
+            port,
+            b"CONNECT api.binance.com:22 HTTP/1.1\r\nHost: api.binance.com\r\n\r\n",
+        )
+        .await;
+        assert!(other_port.starts_with("HTTP/1.1 403"));
+
+        let other_scheme = proxy_response(
+            port,
+            b"GET http://api.binance.com:443/api/v3/ticker HTTP/1.1\r\nHost: api.binance.com\r\n\r\n",
+        )
+        .await;
+        assert!(other_scheme.starts_with("HTTP/1.1 403"));
+
+        proxy_task.abort();
+    }
+}
@@ -0,0 +1,130 @@
+use std::sync::{Arc, RwLock};
+
+use serde::Serialize;
+
+use super::command_line::PrintCommand;
+use crate::model::basic_agents::basic_agents::AgentState;
+
+// what happens while the agents work, in the order it happens
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum AgentEvent {
+    AgentStarted {
+        agent: String,
+    },
+    AgentFinished {
+        agent: String,
+    },
+    StateChanged {
+        agent: String,
+        state: AgentState,
+    },
+    // progress an agent reports, what used to be printed straight to the terminal
+    Action {
+        agent: String,
+        kind: PrintCommand,
+        message: String,
+    },
+    LlmCallStarted {
+        agent: String,
+        operation: String,
+    },
+    LlmCallFinished {
+        agent: String,
+        operation: String,
+        is_success: bool,
+    },
+    BuildFinished {
+        agent: String,
+        is_success: bool,
+    },
+    EndpointTested {
+        agent: String,
+        method: String,
+        route: String,
+        status_code: Option<u16>,
+        is_passed: bool,
+    },
+    UserDecision {
+        is_approved: bool,
+        is_auto_approved: bool,
+    },
+}
+
+// called on the thread of the publishing agent, so it has to be quick
+pub trait EventSubscriber: Send + Sync {
+    fn on_event(&self, event: &AgentEvent);
+}
+
+static SUBSCRIBERS: RwLock<Vec<Arc<dyn EventSubscriber>>> = RwLock::new(Vec::new());
+
+pub fn subscribe(subscriber: Arc<dyn EventSubscriber>) {
+    SUBSCRIBERS
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .push(subscriber);
+}
+
+pub fn publish(event: AgentEvent) {
+    let subscribers = SUBSCRIBERS
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    for subscriber in subscribers.iter() {
+        subscriber.on_event(&event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct EventCollector {
+        events: Mutex<Vec<AgentEvent>>,
+    }
+
+    impl EventSubscriber for EventCollector {
+        fn on_event(&self, event: &AgentEvent) {
+            self.events.lock().unwrap().push(event.clone());
+        }
+    }
+
+    #[test]
+    fn test_publish() {
+        let collector = Arc::new(EventCollector::default());
+        subscribe(collector.clone());
+
+        PrintCommand::UnitTest.print_agent_action("Event Bus Tester", "Building project...");
+
+        // other tests publish on the same bus
+        let events: Vec<AgentEvent> = collector
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|event| {
+                matches!(event, AgentEvent::Action { agent, .. } if agent == "Event Bus Tester")
+            })
+            .cloned()
+            .collect();
+        assert_eq!(
+            events,
+            vec![AgentEvent::Action {
+                agent: "Event Bus Tester".to_string(),
+                kind: PrintCommand::UnitTest,
+                message: "Building project...".to_string(),
+            }]
+        );
+        assert_eq!(
+            serde_json::to_value(&events[0]).unwrap(),
+            serde_json::json!({
+                "event": "action",
+                "agent": "Event Bus Tester",
+                "kind": "unit_test",
+                "message": "Building project...",
+            })
+        );
+    }
+}
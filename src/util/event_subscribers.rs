@@ -0,0 +1,121 @@
+use std::fs::{File, OpenOptions};
+use std::io::{stdout, Write};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crossterm::{
+    style::{Color, ResetColor, SetForegroundColor},
+    ExecutableCommand,
+};
+
+use super::command_line::PrintCommand;
+use super::event_bus::{AgentEvent, EventSubscriber};
+
+// the colored "#AGENT -> ... Stage --> ..." lines of the agents' progress
+pub struct TerminalPrinter;
+
+impl EventSubscriber for TerminalPrinter {
+    fn on_event(&self, event: &AgentEvent) {
+        let AgentEvent::Action {
+            agent,
+            kind,
+            message,
+        } = event
+        else {
+            return;
+        };
+
+        let mut stdout: std::io::Stdout = stdout();
+
+        let current_action_color = match kind {
+            PrintCommand::APICall => Color::DarkBlue,
+            PrintCommand::UnitTest => Color::Green,
+            PrintCommand::Issue => Color::Red,
+        };
+
+        // print the agent's current position in terminal
+        stdout.execute(SetForegroundColor(Color::Magenta)).unwrap();
+
+        print!("#AGENT -> {} ", agent);
+
+        // print agent's comment or statement in color based on it's action
+        stdout
+            .execute(SetForegroundColor(current_action_color))
+            .unwrap();
+
+        print!("Stage --> {} ", message);
+
+        // reset the color in our terminal
+        stdout.execute(ResetColor).unwrap();
+        println!();
+    }
+}
+
+// one json object per event and line, eg. for HOPPITY_EVENT_LOG=events.jsonl
+pub struct JsonLinesLogger {
+    file: Mutex<File>,
+}
+
+impl JsonLinesLogger {
+    pub fn create(path: &str) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+}
+
+impl EventSubscriber for JsonLinesLogger {
+    fn on_event(&self, event: &AgentEvent) {
+        let mut event_line =
+            serde_json::to_value(event).expect("Failed to serialize the agent event");
+        event_line["timestamp_ms"] = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|since_epoch| since_epoch.as_millis() as u64)
+            .unwrap_or_default()
+            .into();
+
+        let mut file = self
+            .file
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        // a full disk should not stop the agents
+        let _ = writeln!(file, "{}", event_line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_lines_logger() {
+        let log_path =
+            std::env::temp_dir().join(format!("hoppity_events_{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&log_path);
+
+        let logger = JsonLinesLogger::create(log_path.to_str().unwrap()).unwrap();
+        logger.on_event(&AgentEvent::BuildFinished {
+            agent: "Backend Developer".to_string(),
+            is_success: true,
+        });
+        logger.on_event(&AgentEvent::UserDecision {
+            is_approved: false,
+            is_auto_approved: false,
+        });
+
+        let log = std::fs::read_to_string(&log_path).unwrap();
+        let events: Vec<serde_json::Value> = log
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let _ = std::fs::remove_file(&log_path);
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["event"], "build_finished");
+        assert_eq!(events[0]["is_success"], true);
+        assert!(events[0]["timestamp_ms"].as_u64().is_some());
+        assert_eq!(events[1]["event"], "user_decision");
+    }
+}
@@ -1,3 +1,14 @@
+pub mod code_policy;
 pub mod command_line;
 pub mod common;
+pub mod egress_proxy;
+pub mod event_bus;
+pub mod event_subscribers;
+pub mod project_runner;
 pub mod provider;
+pub mod route_extractor;
+pub mod run_metrics;
+pub mod sandbox;
+pub mod server_logs;
+pub mod static_server;
+pub mod telemetry;
@@ -0,0 +1,288 @@
+use std::fs;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Output, Stdio};
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+use tokio::net::TcpStream;
+use tokio::task::JoinHandle;
+use tokio::time;
+
+use super::common::{CLIENT_SDK_ROOT_PATH, EXECUTING_PROJECT_ROOT_PATH};
+use super::egress_proxy::{start_egress_proxy, url_target, EgressTarget};
+use super::sandbox::{output_with_timeout, sandboxed_command, SandboxPolicy};
+use super::server_logs::{format_log_lines, LogStream, ServerLogs};
+
+// where the generated server listens when PORT is not set, eg. when the user runs it
+pub const BACKEND_SERVER_URL: &str = "http://localhost:8080";
+// the generated server reads its port from this variable
+pub const BACKEND_SERVER_PORT_ENV: &str = "PORT";
+// the binary is built already, this only covers the server's own startup
+const SERVER_READY_TIMEOUT: Duration = Duration::from_secs(120);
+// log lines attached to the error when the server exits on startup
+const STARTUP_LOG_LINES: usize = 50;
+
+// the sandboxed webserver and the proxy it reaches the approved external urls through
+pub struct BackendServer {
+    pub port: u16,
+    pub url: String,
+    pub logs: ServerLogs,
+    process: Child,
+    egress_proxy: JoinHandle<()>,
+}
+
+impl BackendServer {
+    pub fn kill(&mut self) -> std::io::Result<()> {
+        self.egress_proxy.abort();
+        self.process.kill()
+    }
+
+    pub fn has_exited(&mut self) -> bool {
+        !matches!(self.process.try_wait(), Ok(None))
+    }
+}
+
+// dependencies come from Cargo.toml, which is not AI written, so they are fetched outside of the sandbox
+fn fetch_dependencies(project_root: &str) {
+    let _ = Command::new("cargo")
+        .arg("fetch")
+        .current_dir(project_root)
+        .stderr(Stdio::null())
+        .stdout(Stdio::null())
+        .status();
+}
+
+// cargo build in the project containing main.rs (code generated by openAI), offline and sandboxed
+pub fn build_backend_project() -> Output {
+    fetch_dependencies(EXECUTING_PROJECT_ROOT_PATH);
+
+    let sandbox_policy = SandboxPolicy::offline(EXECUTING_PROJECT_ROOT_PATH);
+    output_with_timeout(
+        sandboxed_command("cargo", &["build", "--offline"], &sandbox_policy),
+        sandbox_policy.limits.wall_time,
+    )
+    .expect("Failed to build the backend project")
+}
+
+// cargo test in the backend project, runs the generated integration tests
+pub fn test_backend_project() -> Output {
+    fetch_dependencies(EXECUTING_PROJECT_ROOT_PATH);
+
+    let sandbox_policy = SandboxPolicy::offline(EXECUTING_PROJECT_ROOT_PATH);
+    output_with_timeout(
+        sandboxed_command("cargo", &["test", "--offline"], &sandbox_policy),
+        sandbox_policy.limits.wall_time,
+    )
+    .expect("Failed to test the backend project")
+}
+
+// cargo check the generated client crate, its Cargo.toml is written by hoppity so fetching is fine
+pub fn check_client_sdk() -> Output {
+    fetch_dependencies(CLIENT_SDK_ROOT_PATH);
+
+    let sandbox_policy = SandboxPolicy::offline(CLIENT_SDK_ROOT_PATH);
+    output_with_timeout(
+        sandboxed_command("cargo", &["check", "--offline"], &sandbox_policy),
+        sandbox_policy.limits.wall_time,
+    )
+    .expect("Failed to check the client crate")
+}
+
+// the binary of the last artifact in cargo's --message-format=json output
+fn server_executable(build_messages: &str) -> Option<PathBuf> {
+    build_messages
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter(|message| message["reason"] == "compiler-artifact")
+        .find_map(|message| message["executable"].as_str().map(PathBuf::from))
+}
+
+// the server runs its binary directly, cargo run would need a writable CARGO_HOME inside the server sandbox
+fn build_server_binary() -> Result<PathBuf, String> {
+    fetch_dependencies(EXECUTING_PROJECT_ROOT_PATH);
+
+    let sandbox_policy = SandboxPolicy::offline(EXECUTING_PROJECT_ROOT_PATH);
+    let build_command_output = output_with_timeout(
+        sandboxed_command(
+            "cargo",
+            &[
+                "build",
+                "--offline",
+                "--message-format=json-render-diagnostics",
+            ],
+            &sandbox_policy,
+        ),
+        sandbox_policy.limits.wall_time,
+    )
+    .expect("Failed to build the backend server");
+
+    if !build_command_output.status.success() {
+        return Err(String::from_utf8_lossy(&build_command_output.stderr).to_string());
+    }
+
+    server_executable(&String::from_utf8_lossy(&build_command_output.stdout))
+        .ok_or("cargo build did not produce a server binary".to_string())
+}
+
+// ask the OS for a port nobody listens on
+fn find_free_port() -> std::io::Result<u16> {
+    Ok(TcpListener::bind("127.0.0.1:0")?.local_addr()?.port())
+}
+
+// poll until the port accepts connections, an early exit comes back with the server's last log lines
+async fn wait_until_ready(
+    process: &mut Child,
+    logs: &ServerLogs,
+    port: u16,
+    timeout: Duration,
+) -> Result<(), String> {
+    let started_at = Instant::now();
+
+    loop {
+        if let Ok(Some(exit_status)) = process.try_wait() {
+            logs.wait_until_closed(Duration::from_secs(1));
+
+            return Err(format!(
+                "The server exited with {} before listening on port {}:\n{}",
+                exit_status,
+                port,
+                format_log_lines(&logs.tail(STARTUP_LOG_LINES))
+            ));
+        }
+
+        if TcpStream::connect(("127.0.0.1", port)).await.is_ok() {
+            return Ok(());
+        }
+
+        if started_at.elapsed() > timeout {
+            let _ = process.kill();
+            return Err(format!(
+                "The server did not listen on port {} within {}s, it has to read the port from the {} environment variable",
+                port,
+                timeout.as_secs(),
+                BACKEND_SERVER_PORT_ENV
+            ));
+        }
+
+        time::sleep(Duration::from_millis(250)).await;
+    }
+}
+
+// build and run the backend project on a free port and wait until the webserver accepts connections
+// outbound traffic is only allowed to the hosts of the approved external urls
+pub async fn start_backend_server(external_urls: &[String]) -> Result<BackendServer, String> {
+    let server_executable = build_server_binary()?;
+
+    // the server may only write under data/, it has to exist to be mounted writable
+    fs::create_dir_all(Path::new(EXECUTING_PROJECT_ROOT_PATH).join("data"))
+        .expect("Failed to create the server data dir");
+
+    let allowed_targets: Vec<EgressTarget> = external_urls
+        .iter()
+        .filter_map(|url| url_target(url))
+        .collect();
+
+    let (egress_proxy_port, egress_proxy) = start_egress_proxy(allowed_targets)
+        .await
+        .expect("Failed to start the egress proxy");
+    let egress_proxy_url = format!("http://127.0.0.1:{}", egress_proxy_port);
+
+    let port = find_free_port().expect("Failed to find a free port");
+    let sandbox_policy =
+        SandboxPolicy::server(EXECUTING_PROJECT_ROOT_PATH, port, egress_proxy_port);
+
+    let mut process = sandboxed_command(
+        &server_executable.display().to_string(),
+        &[],
+        &sandbox_policy,
+    )
+    .env("HTTP_PROXY", &egress_proxy_url)
+    .env("HTTPS_PROXY", &egress_proxy_url)
+    .env("http_proxy", &egress_proxy_url)
+    .env("https_proxy", &egress_proxy_url)
+    .env("NO_PROXY", "localhost,127.0.0.1")
+    .env(BACKEND_SERVER_PORT_ENV, port.to_string())
+    .stderr(Stdio::piped())
+    .stdout(Stdio::piped())
+    .spawn()
+    .expect("Failed to run the server");
+
+    let logs = ServerLogs::new();
+    logs.capture(process.stdout.take(), LogStream::Stdout);
+    logs.capture(process.stderr.take(), LogStream::Stderr);
+
+    if let Err(server_error) =
+        wait_until_ready(&mut process, &logs, port, SERVER_READY_TIMEOUT).await
+    {
+        egress_proxy.abort();
+        return Err(server_error);
+    }
+
+    Ok(BackendServer {
+        port,
+        url: format!("http://localhost:{}", port),
+        logs,
+        process,
+        egress_proxy,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_server_executable() {
+        let build_messages = r#"{"reason":"compiler-artifact","target":{"kind":["lib"]},"executable":null}
+{"reason":"compiler-artifact","target":{"kind":["bin"]},"executable":"/tmp/hoppity-bin/target/debug/hoppity-bin"}
+{"reason":"build-finished","success":true}"#;
+
+        assert_eq!(
+            server_executable(build_messages),
+            Some(PathBuf::from("/tmp/hoppity-bin/target/debug/hoppity-bin"))
+        );
+        assert_eq!(server_executable("not json"), None);
+    }
+
+    #[tokio::test]
+    async fn test_wait_until_ready() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let mut running_process = Command::new("sleep").arg("5").spawn().unwrap();
+        let ready_result = wait_until_ready(
+            &mut running_process,
+            &ServerLogs::new(),
+            port,
+            Duration::from_secs(2),
+        )
+        .await;
+        running_process.kill().unwrap();
+
+        assert_eq!(ready_result, Ok(()));
+    }
+
+    #[tokio::test]
+    async fn test_wait_until_ready_early_exit() {
+        let port = find_free_port().unwrap();
+
+        let mut crashed_process = Command::new("sh")
+            .args(["-c", "echo 'address already in use' >&2; exit 3"])
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        let logs = ServerLogs::new();
+        logs.capture(crashed_process.stderr.take(), LogStream::Stderr);
+
+        let server_error =
+            wait_until_ready(&mut crashed_process, &logs, port, Duration::from_secs(5))
+                .await
+                .unwrap_err();
+
+        assert!(server_error.contains("exit status: 3"));
+        assert!(server_error.contains("address already in use"));
+    }
+}
@@ -0,0 +1,806 @@
+use std::collections::{BTreeMap, HashMap};
+
+use syn::ext::IdentExt;
+use syn::visit::{self, Visit};
+use syn::{
+    Expr, ExprCall, ExprMethodCall, Fields, FnArg, GenericArgument, Item, ItemEnum, ItemFn,
+    ItemStruct, Lit, Local, Pat, PathArguments, ReturnType, Type,
+};
+
+use crate::model::common::api_schema::{FormattedRouteObject, HttpMethod, SchemaType};
+
+// structs nested deeper than this are left as their name
+const MAX_SCHEMA_DEPTH: usize = 8;
+
+const ROUTE_MACROS: [&str; 7] = ["get", "post", "put", "patch", "delete", "head", "options"];
+
+// json shapes of the structs and enums declared in the code, by name
+#[derive(Default)]
+struct TypeShapes {
+    structs: HashMap<String, ItemStruct>,
+    unit_enums: Vec<String>,
+}
+
+// a route registration found in the code, the handler is resolved afterwards
+#[derive(Debug, Clone, PartialEq)]
+struct RouteRegistration {
+    route: String,
+    method: HttpMethod,
+    handler: String,
+}
+
+fn last_segment(path: &syn::Path) -> String {
+    path.segments
+        .last()
+        .map(|segment| segment.ident.to_string())
+        .unwrap_or_default()
+}
+
+fn string_literal(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Lit(expr_lit) => match &expr_lit.lit {
+            Lit::Str(lit_str) => Some(lit_str.value()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// "/api" + "/item" -> "/api/item"
+fn join_route(prefix: &str, route: &str) -> String {
+    let joined = format!(
+        "{}/{}",
+        prefix.trim_end_matches('/'),
+        route.trim_start_matches('/')
+    );
+    let joined = joined.trim_end_matches('/');
+
+    if joined.is_empty() {
+        "/".to_string()
+    } else {
+        joined.to_string()
+    }
+}
+
+// Option<T> -> T
+fn option_type(ty: &Type) -> Option<&Type> {
+    match ty {
+        Type::Path(type_path) if last_segment(&type_path.path) == "Option" => {
+            generic_type(&type_path.path)
+        }
+        _ => None,
+    }
+}
+
+// the generic argument of eg. web::Json<Item> or Vec<Item>
+fn generic_type(path: &syn::Path) -> Option<&Type> {
+    let PathArguments::AngleBracketed(arguments) = &path.segments.last()?.arguments else {
+        return None;
+    };
+
+    arguments.args.iter().find_map(|argument| match argument {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    })
+}
+
+// serde attributes that change the json of a field
+#[derive(Debug, Default)]
+struct SerdeFieldAttrs {
+    rename: Option<String>,
+    is_skipped: bool,
+    is_flattened: bool,
+    // skip_serializing_if leaves the key out of some responses
+    is_optional: bool,
+}
+
+fn serde_field_attrs(field: &syn::Field) -> SerdeFieldAttrs {
+    let mut field_attrs = SerdeFieldAttrs::default();
+
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("serde"))
+    {
+        let _ = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") || meta.path.is_ident("skip_serializing") {
+                field_attrs.is_skipped = true;
+            } else if meta.path.is_ident("flatten") {
+                field_attrs.is_flattened = true;
+            } else if meta.path.is_ident("rename") {
+                let renamed: syn::LitStr = meta.value()?.parse()?;
+                field_attrs.rename = Some(renamed.value());
+            } else if meta.path.is_ident("skip_serializing_if") {
+                field_attrs.is_optional = true;
+                let _: Expr = meta.value()?.parse()?;
+            } else if meta.input.peek(syn::Token![=]) {
+                // default = ".." and the like
+                let _: Expr = meta.value()?.parse()?;
+            }
+            Ok(())
+        });
+    }
+
+    field_attrs
+}
+
+// #[serde(rename_all = "camelCase")] on the struct
+fn serde_rename_all(item_struct: &ItemStruct) -> Option<String> {
+    let mut rename_all = None;
+
+    for attr in item_struct
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("serde"))
+    {
+        let _ = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename_all") && meta.input.peek(syn::Token![=]) {
+                let rename_rule: syn::LitStr = meta.value()?.parse()?;
+                rename_all = Some(rename_rule.value());
+            } else if meta.input.peek(syn::Token![=]) {
+                let _: Expr = meta.value()?.parse()?;
+            } else if meta.input.peek(syn::token::Paren) {
+                // rename_all(serialize = ".."), deny_unknown_fields and the like are not followed
+                meta.parse_nested_meta(|nested_meta| {
+                    let _: Expr = nested_meta.value()?.parse()?;
+                    Ok(())
+                })?;
+            }
+            Ok(())
+        });
+    }
+
+    rename_all
+}
+
+// a snake_case field name under a serde rename_all rule, eg. "is_done" + "camelCase" -> "isDone"
+fn apply_rename_rule(field_name: &str, rename_rule: &str) -> String {
+    let pascal_case = || -> String {
+        field_name
+            .split('_')
+            .map(|word| {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                    .unwrap_or_default()
+            })
+            .collect()
+    };
+
+    match rename_rule {
+        "lowercase" => field_name.to_lowercase(),
+        "UPPERCASE" | "SCREAMING_SNAKE_CASE" => field_name.to_uppercase(),
+        "PascalCase" => pascal_case(),
+        "camelCase" => {
+            let pascal_name = pascal_case();
+            let mut chars = pascal_name.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_lowercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        }
+        "kebab-case" => field_name.replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => field_name.replace('_', "-").to_uppercase(),
+        _ => field_name.to_string(),
+    }
+}
+
+impl TypeShapes {
+    fn new(file: &syn::File) -> Self {
+        let mut type_shapes = TypeShapes::default();
+
+        for item in &file.items {
+            match item {
+                Item::Struct(item_struct) => {
+                    type_shapes
+                        .structs
+                        .insert(item_struct.ident.to_string(), item_struct.clone());
+                }
+                Item::Enum(ItemEnum {
+                    ident, variants, ..
+                }) if variants
+                    .iter()
+                    .all(|variant| matches!(variant.fields, Fields::Unit)) =>
+                {
+                    type_shapes.unit_enums.push(ident.to_string());
+                }
+                _ => {}
+            }
+        }
+
+        type_shapes
+    }
+
+    fn struct_schema(&self, item_struct: &ItemStruct, depth: usize) -> SchemaType {
+        match &item_struct.fields {
+            Fields::Named(fields) => {
+                let rename_all = serde_rename_all(item_struct);
+                let mut object_fields: BTreeMap<String, SchemaType> = BTreeMap::new();
+
+                for field in &fields.named {
+                    let field_attrs = serde_field_attrs(field);
+                    let Some(ident) = field.ident.as_ref() else {
+                        continue;
+                    };
+                    if field_attrs.is_skipped {
+                        continue;
+                    }
+
+                    // the fields of a flattened struct sit next to the others
+                    if field_attrs.is_flattened {
+                        if let SchemaType::Object(flattened_fields) =
+                            self.type_schema(&field.ty, depth + 1)
+                        {
+                            object_fields.extend(flattened_fields);
+                        }
+                        continue;
+                    }
+
+                    let field_name = field_attrs.rename.unwrap_or_else(|| {
+                        let field_name = ident.unraw().to_string();
+                        match &rename_all {
+                            Some(rename_rule) => apply_rename_rule(&field_name, rename_rule),
+                            None => field_name,
+                        }
+                    });
+
+                    let field_schema = match option_type(&field.ty) {
+                        Some(inner_type) => {
+                            SchemaType::Optional(Box::new(self.type_schema(inner_type, depth + 1)))
+                        }
+                        None if field_attrs.is_optional => {
+                            SchemaType::Optional(Box::new(self.type_schema(&field.ty, depth + 1)))
+                        }
+                        None => self.type_schema(&field.ty, depth + 1),
+                    };
+
+                    object_fields.insert(field_name, field_schema);
+                }
+
+                SchemaType::Object(object_fields)
+            }
+            // newtype structs serialize as what they wrap
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                self.type_schema(&fields.unnamed[0].ty, depth + 1)
+            }
+            _ => SchemaType::NotProvided,
+        }
+    }
+
+    // the json a rust type serializes to
+    fn type_schema(&self, ty: &Type, depth: usize) -> SchemaType {
+        if depth > MAX_SCHEMA_DEPTH {
+            return SchemaType::NotProvided;
+        }
+
+        match ty {
+            Type::Reference(reference) => self.type_schema(&reference.elem, depth),
+            Type::Paren(paren) => self.type_schema(&paren.elem, depth),
+            Type::Slice(slice) => SchemaType::Array(Box::new(self.type_schema(&slice.elem, depth))),
+            Type::Array(array) => SchemaType::Array(Box::new(self.type_schema(&array.elem, depth))),
+            Type::Path(type_path) => {
+                let type_name = last_segment(&type_path.path);
+                let inner_schema = || {
+                    generic_type(&type_path.path)
+                        .map(|inner| self.type_schema(inner, depth))
+                        .unwrap_or(SchemaType::NotProvided)
+                };
+
+                match type_name.as_str() {
+                    "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32"
+                    | "i64" | "i128" | "isize" => SchemaType::Number,
+                    "f32" | "f64" => SchemaType::Float,
+                    "bool" => SchemaType::Bool,
+                    "String" | "str" | "char" | "Uuid" => SchemaType::String,
+                    "Vec" | "VecDeque" | "HashSet" | "BTreeSet" => {
+                        SchemaType::Array(Box::new(inner_schema()))
+                    }
+                    "Box" | "Arc" | "Rc" | "Json" => inner_schema(),
+                    // a missing value is null, the schema cannot say "number or null"
+                    "Option" => SchemaType::NotProvided,
+                    "HashMap" | "BTreeMap" => SchemaType::Object(BTreeMap::new()),
+                    "Value" => SchemaType::NotProvided,
+                    _ => match self.structs.get(&type_name) {
+                        Some(item_struct) => self.struct_schema(item_struct, depth),
+                        None if self.unit_enums.contains(&type_name) => SchemaType::String,
+                        None => SchemaType::Other(type_name),
+                    },
+                }
+            }
+            _ => SchemaType::NotProvided,
+        }
+    }
+}
+
+// `web::get().to(handler)` -> (GET, "handler")
+fn route_target(expr: &Expr) -> Option<(HttpMethod, String)> {
+    let Expr::MethodCall(to_call) = expr else {
+        return None;
+    };
+    if to_call.method != "to" {
+        return route_target(&to_call.receiver);
+    }
+
+    let Expr::Path(handler_path) = to_call.args.first()? else {
+        return None;
+    };
+
+    // guards may sit between web::get() and .to(..)
+    let mut receiver = &*to_call.receiver;
+    while let Expr::MethodCall(method_call) = receiver {
+        receiver = &method_call.receiver;
+    }
+
+    let Expr::Call(ExprCall { func, .. }) = receiver else {
+        return None;
+    };
+    let Expr::Path(method_path) = &**func else {
+        return None;
+    };
+
+    let method = HttpMethod::try_from(last_segment(&method_path.path)).ok()?;
+    Some((method, last_segment(&handler_path.path)))
+}
+
+// the call a builder chain starts with, eg. web::scope("/api") for web::scope("/api").route(..)
+fn chain_root(method_call: &ExprMethodCall) -> Option<(String, String)> {
+    let mut receiver = &*method_call.receiver;
+    while let Expr::MethodCall(inner_call) = receiver {
+        receiver = &inner_call.receiver;
+    }
+
+    let Expr::Call(ExprCall { func, args, .. }) = receiver else {
+        return None;
+    };
+    let Expr::Path(func_path) = &**func else {
+        return None;
+    };
+
+    Some((
+        last_segment(&func_path.path),
+        string_literal(args.first()?)?,
+    ))
+}
+
+#[derive(Debug, Default)]
+struct RouteVisitor {
+    // prefixes of the scopes the visitor is inside of
+    scope_prefixes: Vec<String>,
+    registrations: Vec<RouteRegistration>,
+    // handlers passed to .service(..), they carry their route in #[get("/x")]
+    services: Vec<(String, String)>,
+}
+
+impl RouteVisitor {
+    fn prefix(&self) -> String {
+        self.scope_prefixes
+            .iter()
+            .fold(String::new(), |prefix, scope| join_route(&prefix, scope))
+    }
+}
+
+impl<'ast> Visit<'ast> for RouteVisitor {
+    fn visit_expr_method_call(&mut self, method_call: &'ast ExprMethodCall) {
+        let root = chain_root(method_call);
+        let chain_prefix = match &root {
+            Some((root_name, root_route)) if root_name == "scope" => {
+                join_route(&self.prefix(), root_route)
+            }
+            _ => self.prefix(),
+        };
+
+        match (
+            method_call.method.to_string().as_str(),
+            method_call.args.len(),
+        ) {
+            // .route("/item", web::get().to(get_items))
+            ("route", 2) => {
+                if let (Some(route), Some((method, handler))) = (
+                    string_literal(&method_call.args[0]),
+                    route_target(&method_call.args[1]),
+                ) {
+                    self.registrations.push(RouteRegistration {
+                        route: join_route(&chain_prefix, &route),
+                        method,
+                        handler,
+                    });
+                }
+            }
+            // web::resource("/item").route(web::get().to(get_items))
+            ("route", 1) => {
+                if let (Some((root_name, root_route)), Some((method, handler))) =
+                    (&root, route_target(&method_call.args[0]))
+                {
+                    if root_name == "resource" {
+                        self.registrations.push(RouteRegistration {
+                            route: join_route(&self.prefix(), root_route),
+                            method,
+                            handler,
+                        });
+                    }
+                }
+            }
+            ("service", _) => {
+                for arg in &method_call.args {
+                    if let Expr::Path(handler_path) = arg {
+                        self.services
+                            .push((chain_prefix.clone(), last_segment(&handler_path.path)));
+                    }
+                }
+
+                // routes inside the service are relative to the scope it is added to
+                self.visit_expr(&method_call.receiver);
+                self.scope_prefixes.push(chain_prefix.clone());
+                for arg in &method_call.args {
+                    self.visit_expr(arg);
+                }
+                self.scope_prefixes.pop();
+                return;
+            }
+            _ => {}
+        }
+
+        visit::visit_expr_method_call(self, method_call);
+    }
+}
+
+// #[get("/item")] -> (GET, "/item")
+fn route_macro(item_fn: &ItemFn) -> Option<(HttpMethod, String)> {
+    item_fn.attrs.iter().find_map(|attr| {
+        let macro_name = last_segment(attr.path());
+        if !ROUTE_MACROS.contains(&macro_name.as_str()) {
+            return None;
+        }
+
+        let route: syn::LitStr = attr
+            .parse_args_with(|input: syn::parse::ParseStream| {
+                let route: syn::LitStr = input.parse()?;
+                // extra arguments like guard = ".." are not part of the route
+                let _: proc_macro2::TokenStream = input.parse()?;
+                Ok(route)
+            })
+            .ok()?;
+
+        Some((HttpMethod::try_from(macro_name).ok()?, route.value()))
+    })
+}
+
+// the body a handler extracts with web::Json<T> or web::Form<T>
+fn request_body_schema(item_fn: &ItemFn, type_shapes: &TypeShapes) -> SchemaType {
+    item_fn
+        .sig
+        .inputs
+        .iter()
+        .find_map(|input| {
+            let FnArg::Typed(pat_type) = input else {
+                return None;
+            };
+            let Type::Path(type_path) = &*pat_type.ty else {
+                return None;
+            };
+
+            matches!(last_segment(&type_path.path).as_str(), "Json" | "Form")
+                .then(|| generic_type(&type_path.path))
+                .flatten()
+                .map(|body_type| type_shapes.type_schema(body_type, 0))
+        })
+        .unwrap_or(SchemaType::None)
+}
+
+// the value passed to .json(..) or web::Json(..), with the types of annotated lets in scope
+#[derive(Default)]
+struct ResponseVisitor {
+    local_types: HashMap<String, Type>,
+    response_exprs: Vec<Expr>,
+}
+
+impl<'ast> Visit<'ast> for ResponseVisitor {
+    fn visit_local(&mut self, local: &'ast Local) {
+        if let Pat::Type(pat_type) = &local.pat {
+            if let Pat::Ident(pat_ident) = &*pat_type.pat {
+                self.local_types
+                    .insert(pat_ident.ident.to_string(), (*pat_type.ty).clone());
+            }
+        }
+        visit::visit_local(self, local);
+    }
+
+    fn visit_expr_method_call(&mut self, method_call: &'ast ExprMethodCall) {
+        if method_call.method == "json" && method_call.args.len() == 1 {
+            self.response_exprs.push(method_call.args[0].clone());
+        }
+        visit::visit_expr_method_call(self, method_call);
+    }
+
+    fn visit_expr_call(&mut self, call: &'ast ExprCall) {
+        if let Expr::Path(func_path) = &*call.func {
+            if last_segment(&func_path.path) == "Json" && call.args.len() == 1 {
+                self.response_exprs.push(call.args[0].clone());
+            }
+        }
+        visit::visit_expr_call(self, call);
+    }
+}
+
+fn expr_schema(
+    expr: &Expr,
+    local_types: &HashMap<String, Type>,
+    type_shapes: &TypeShapes,
+) -> SchemaType {
+    match expr {
+        Expr::Reference(reference) => expr_schema(&reference.expr, local_types, type_shapes),
+        Expr::Paren(paren) => expr_schema(&paren.expr, local_types, type_shapes),
+        Expr::Struct(expr_struct) => type_shapes.type_schema(
+            &Type::Path(syn::TypePath {
+                qself: None,
+                path: expr_struct.path.clone(),
+            }),
+            0,
+        ),
+        Expr::Path(expr_path) => local_types
+            .get(&last_segment(&expr_path.path))
+            .map(|ty| type_shapes.type_schema(ty, 0))
+            .unwrap_or(SchemaType::NotProvided),
+        _ => SchemaType::NotProvided,
+    }
+}
+
+// web::Json<T> in the return type, else the first .json(..) whose value has a known type
+fn response_schema(item_fn: &ItemFn, type_shapes: &TypeShapes) -> SchemaType {
+    if let ReturnType::Type(_, return_type) = &item_fn.sig.output {
+        let mut return_type = &**return_type;
+
+        // Result<web::Json<T>, E>
+        if let Type::Path(type_path) = return_type {
+            if last_segment(&type_path.path) == "Result" {
+                if let Some(ok_type) = generic_type(&type_path.path) {
+                    return_type = ok_type;
+                }
+            }
+        }
+
+        if let Type::Path(type_path) = return_type {
+            if last_segment(&type_path.path) == "Json" {
+                return type_shapes.type_schema(return_type, 0);
+            }
+        }
+    }
+
+    let mut response_visitor = ResponseVisitor::default();
+    response_visitor.visit_block(&item_fn.block);
+
+    response_visitor
+        .response_exprs
+        .iter()
+        .map(|expr| expr_schema(expr, &response_visitor.local_types, type_shapes))
+        .find(|schema| !schema.is_empty())
+        .unwrap_or(SchemaType::NotProvided)
+}
+
+// routes of an actix-web server read straight from its code
+// None when the code does not parse or registers no route this understands
+pub fn extract_actix_routes(code: &str) -> Option<Vec<FormattedRouteObject>> {
+    let file = syn::parse_file(code).ok()?;
+    let type_shapes = TypeShapes::new(&file);
+
+    let handlers: HashMap<String, &ItemFn> = file
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Fn(item_fn) => Some((item_fn.sig.ident.to_string(), item_fn)),
+            _ => None,
+        })
+        .collect();
+
+    let mut route_visitor = RouteVisitor::default();
+    route_visitor.visit_file(&file);
+
+    let mut registrations = route_visitor.registrations;
+
+    // #[get("/x")] handlers in source order, under the scope they are added to
+    for item in &file.items {
+        let Item::Fn(item_fn) = item else {
+            continue;
+        };
+        let Some((method, route)) = route_macro(item_fn) else {
+            continue;
+        };
+        let handler_name = item_fn.sig.ident.to_string();
+
+        let scope_prefix = route_visitor
+            .services
+            .iter()
+            .find(|(_, service)| *service == handler_name)
+            .map(|(scope_prefix, _)| scope_prefix.clone())
+            .unwrap_or_default();
+
+        registrations.push(RouteRegistration {
+            route: join_route(&scope_prefix, &route),
+            method,
+            handler: handler_name,
+        });
+    }
+
+    let mut api_schema: Vec<FormattedRouteObject> = Vec::new();
+    for registration in registrations {
+        let is_known_route = api_schema.iter().any(|route_obj| {
+            route_obj.route == registration.route && route_obj.method == registration.method
+        });
+        if is_known_route {
+            continue;
+        }
+
+        let (request_body, response) = match handlers.get(&registration.handler) {
+            Some(item_fn) => (
+                request_body_schema(item_fn, &type_shapes),
+                response_schema(item_fn, &type_shapes),
+            ),
+            None => (SchemaType::None, SchemaType::NotProvided),
+        };
+
+        api_schema.push(FormattedRouteObject::new(
+            &registration.route,
+            registration.method,
+            request_body,
+            response,
+        ));
+    }
+
+    (!api_schema.is_empty()).then_some(api_schema)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validation::response_shape::check_response_shape;
+    use serde_json::json;
+
+    const ACTIX_CODE: &str = r#"
+        use actix_web::{get, web, App, HttpResponse, HttpServer, Responder};
+        use serde::{Deserialize, Serialize};
+
+        #[derive(Serialize, Deserialize, Clone)]
+        struct Item {
+            id: u64,
+            name: String,
+            #[serde(rename = "isDone")]
+            completed: bool,
+            tags: Vec<String>,
+            #[serde(skip)]
+            cache: Option<String>,
+        }
+
+        #[derive(Deserialize)]
+        struct NewItem {
+            name: String,
+        }
+
+        async fn get_items(data: web::Data<AppState>) -> impl Responder {
+            let items: Vec<Item> = data.items.lock().unwrap().clone();
+            HttpResponse::Ok().json(items)
+        }
+
+        async fn create_item(item: web::Json<NewItem>) -> web::Json<Item> {
+            web::Json(Item { id: 1, name: item.name.clone(), completed: false, tags: vec![], cache: None })
+        }
+
+        async fn delete_item(path: web::Path<u64>) -> impl Responder {
+            HttpResponse::Ok().finish()
+        }
+
+        #[get("/version")]
+        async fn version() -> impl Responder {
+            HttpResponse::Ok().body("1.0.0")
+        }
+
+        #[get("/health")]
+        async fn health() -> impl Responder {
+            HttpResponse::Ok().json(serde_json::json!({ "status": "ok" }))
+        }
+
+        #[actix_web::main]
+        async fn main() -> std::io::Result<()> {
+            HttpServer::new(move || {
+                App::new()
+                    .route("/item", web::post().to(create_item))
+                    .service(version)
+                    .service(
+                        web::scope("/api")
+                            .route("/items", web::get().to(get_items))
+                            .service(web::resource("/item/{id}").route(web::delete().to(delete_item)))
+                            .service(health),
+                    )
+            })
+            .bind(("127.0.0.1", 8080))?
+            .run()
+            .await
+        }
+    "#;
+
+    #[test]
+    fn test_extract_actix_routes() {
+        let api_schema = extract_actix_routes(ACTIX_CODE).unwrap();
+
+        let routes: Vec<String> = api_schema
+            .iter()
+            .map(|route_obj| format!("{} {}", route_obj.method, route_obj.route))
+            .collect();
+        assert_eq!(
+            routes,
+            vec![
+                "POST /item",
+                "GET /api/items",
+                "DELETE /api/item/{id}",
+                "GET /version",
+                "GET /api/health"
+            ]
+        );
+
+        let item =
+            json!({ "id": "number", "name": "string", "isDone": "bool", "tags": ["string"] });
+        assert_eq!(
+            api_schema[0].request_body,
+            SchemaType::from(json!({ "name": "string" }))
+        );
+        assert_eq!(api_schema[0].response, SchemaType::from(item.clone()));
+        assert_eq!(api_schema[1].response, SchemaType::from(json!([item])));
+        assert_eq!(api_schema[2].path_params, vec!["id".to_string()]);
+        assert_eq!(api_schema[2].request_body, SchemaType::None);
+        assert_eq!(api_schema[4].response, SchemaType::NotProvided);
+
+        assert_eq!(extract_actix_routes("fn main() {}"), None);
+        assert_eq!(extract_actix_routes("fn main( {"), None);
+    }
+
+    #[test]
+    fn test_serde_container_attributes() {
+        let code = r#"
+            #[derive(Serialize)]
+            struct Audit {
+                created_at: String,
+            }
+
+            #[derive(Serialize)]
+            #[serde(rename_all = "camelCase")]
+            struct Profile {
+                user_id: u64,
+                #[serde(rename = "display")]
+                display_name: String,
+                avatar_url: Option<String>,
+                #[serde(skip_serializing_if = "Vec::is_empty")]
+                recent_tags: Vec<String>,
+                #[serde(flatten)]
+                audit: Audit,
+            }
+
+            async fn profile() -> web::Json<Profile> {
+                todo!()
+            }
+
+            fn main() {
+                App::new().route("/profile", web::get().to(profile));
+            }
+        "#;
+
+        let api_schema = extract_actix_routes(code).unwrap();
+
+        assert_eq!(
+            api_schema[0].response,
+            SchemaType::from(json!({
+                "userId": "number",
+                "display": "string",
+                "avatarUrl?": "string",
+                "recentTags?": ["string"],
+                "created_at": "string"
+            }))
+        );
+        assert!(check_response_shape(
+            &api_schema[0].response,
+            Some(
+                &json!({ "userId": 1, "display": "bunny", "avatarUrl": null, "created_at": "now" })
+            )
+        )
+        .is_empty());
+    }
+}
@@ -0,0 +1,73 @@
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::common::large_language_model::ChatCompletionUsage;
+
+// metrics collected while the agents work on a single user request
+// used by `hoppity eval` to compare prompt changes against a baseline
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct RunMetrics {
+    pub is_scope_decoded: bool,
+    pub is_compiled_first_try: Option<bool>,
+    pub build_attempts: u32,
+    pub fix_iterations: u32,
+    pub endpoints_tested: u32,
+    pub endpoints_passed: u32,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+}
+
+static RUN_METRICS: OnceLock<Mutex<RunMetrics>> = OnceLock::new();
+
+fn run_metrics() -> MutexGuard<'static, RunMetrics> {
+    RUN_METRICS
+        .get_or_init(|| Mutex::new(RunMetrics::default()))
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+// start collecting metrics for a new run
+pub fn reset_run_metrics() {
+    *run_metrics() = RunMetrics::default();
+}
+
+// snapshot of the metrics collected so far
+pub fn get_run_metrics() -> RunMetrics {
+    run_metrics().clone()
+}
+
+pub fn record_scope_decoded() {
+    run_metrics().is_scope_decoded = true;
+}
+
+pub fn record_build_result(is_success: bool) {
+    let mut metrics = run_metrics();
+
+    if metrics.is_compiled_first_try.is_none() {
+        metrics.is_compiled_first_try = Some(is_success);
+    }
+    metrics.build_attempts += 1;
+}
+
+pub fn record_fix_iteration() {
+    run_metrics().fix_iterations += 1;
+}
+
+pub fn record_endpoint_result(is_success: bool) {
+    let mut metrics = run_metrics();
+
+    metrics.endpoints_tested += 1;
+    if is_success {
+        metrics.endpoints_passed += 1;
+    }
+}
+
+pub fn record_token_usage(usage: &ChatCompletionUsage) {
+    let mut metrics = run_metrics();
+
+    metrics.prompt_tokens += usage.prompt_tokens;
+    metrics.completion_tokens += usage.completion_tokens;
+    metrics.total_tokens += usage.total_tokens;
+}
@@ -0,0 +1,747 @@
+use std::fs::{File, OpenOptions};
+use std::io::Read;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::sync::OnceLock;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::command_line::PrintCommand;
+
+// `hoppity sandbox-exec ... -- program args` applies the limits and then becomes the program
+pub const SANDBOX_EXEC_COMMAND: &str = "sandbox-exec";
+const ALLOW_UNSANDBOXED_ENV: &str = "HOPPITY_ALLOW_UNSANDBOXED";
+
+// always writable inside the sandbox, /tmp is a fresh tmpfs under bubblewrap
+const SCRATCH_WRITE_PATHS: [&str; 2] = ["/tmp", "/dev"];
+
+// landlock uapi, see include/uapi/linux/landlock.h
+const LANDLOCK_CREATE_RULESET_VERSION: libc::c_uint = 1 << 0;
+const LANDLOCK_RULE_PATH_BENEATH: libc::c_int = 1;
+const LANDLOCK_RULE_NET_PORT: libc::c_int = 2;
+const LANDLOCK_ACCESS_FS_WRITE_FILE: u64 = 1 << 1;
+// every right that creates, changes or removes a file, reading stays allowed everywhere
+const LANDLOCK_ACCESS_FS_WRITE: u64 = LANDLOCK_ACCESS_FS_WRITE_FILE
+    | (1 << 4)
+    | (1 << 5)
+    | (1 << 6)
+    | (1 << 7)
+    | (1 << 8)
+    | (1 << 9)
+    | (1 << 10)
+    | (1 << 11)
+    | (1 << 12);
+// truncate(2) is its own right since landlock ABI 3 (linux 6.2), unhandled it stays allowed everywhere
+const LANDLOCK_ACCESS_FS_TRUNCATE: u64 = 1 << 14;
+const LANDLOCK_TRUNCATE_ABI: i64 = 3;
+// landlock only has tcp rights, udp (and with it dns) is never restricted
+const LANDLOCK_ACCESS_NET_BIND_TCP: u64 = 1 << 0;
+const LANDLOCK_ACCESS_NET_CONNECT_TCP: u64 = 1 << 1;
+// network rules need landlock ABI 4 (linux 6.7)
+const LANDLOCK_NET_ABI: i64 = 4;
+
+#[repr(C)]
+struct LandlockRulesetAttr {
+    handled_access_fs: u64,
+    handled_access_net: u64,
+}
+
+#[repr(C, packed)]
+struct LandlockPathBeneathAttr {
+    allowed_access: u64,
+    parent_fd: i32,
+}
+
+#[repr(C)]
+struct LandlockNetPortAttr {
+    allowed_access: u64,
+    port: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SandboxTool {
+    Bubblewrap,
+    Unshare,
+    Unsandboxed,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SandboxLimits {
+    pub cpu_seconds: u64,
+    pub memory_bytes: u64,
+    pub wall_time: Duration,
+}
+
+impl Default for SandboxLimits {
+    fn default() -> Self {
+        Self {
+            cpu_seconds: 600,
+            memory_bytes: 4 * 1024 * 1024 * 1024,
+            wall_time: Duration::from_secs(900),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SandboxPolicy {
+    pub project_root: PathBuf,
+    pub writable_paths: Vec<PathBuf>,
+    pub is_network_isolated: bool,
+    pub bind_ports: Vec<u16>,
+    pub connect_ports: Vec<u16>,
+    pub limits: SandboxLimits,
+}
+
+// what the sandbox-exec launcher has to apply before it executes the program
+#[derive(Debug, Clone, PartialEq)]
+pub struct SandboxExecOptions {
+    pub cpu_seconds: u64,
+    pub memory_bytes: u64,
+    pub write_paths: Vec<PathBuf>,
+    pub bind_ports: Vec<u16>,
+    pub connect_ports: Vec<u16>,
+    pub is_network_isolated: bool,
+}
+
+fn cargo_home() -> PathBuf {
+    std::env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cargo")))
+        .unwrap_or_else(|| PathBuf::from("/root/.cargo"))
+}
+
+impl SandboxPolicy {
+    // builds and tests: no network at all, dependencies are fetched beforehand
+    // cargo keeps its locks under CARGO_HOME, so only cargo itself gets to write there
+    pub fn offline(project_root: &str) -> Self {
+        Self {
+            project_root: PathBuf::from(project_root),
+            writable_paths: vec![PathBuf::from(project_root), cargo_home()],
+            is_network_isolated: true,
+            bind_ports: Vec::new(),
+            connect_ports: Vec::new(),
+            limits: SandboxLimits::default(),
+        }
+    }
+
+    // the built server shares the network to be testable, but may only listen on its own port
+    // and connect to the egress proxy, it only writes its data/ and target/ dirs
+    pub fn server(project_root: &str, bind_port: u16, egress_proxy_port: u16) -> Self {
+        let project_root = PathBuf::from(project_root);
+
+        Self {
+            writable_paths: vec![project_root.join("data"), project_root.join("target")],
+            project_root,
+            is_network_isolated: false,
+            bind_ports: vec![bind_port],
+            connect_ports: vec![egress_proxy_port],
+            limits: SandboxLimits::default(),
+        }
+    }
+
+    fn exec_options(&self) -> SandboxExecOptions {
+        SandboxExecOptions {
+            cpu_seconds: self.limits.cpu_seconds,
+            memory_bytes: self.limits.memory_bytes,
+            write_paths: self
+                .writable_paths
+                .iter()
+                .cloned()
+                .chain(SCRATCH_WRITE_PATHS.iter().map(PathBuf::from))
+                .collect(),
+            bind_ports: self.bind_ports.clone(),
+            connect_ports: self.connect_ports.clone(),
+            is_network_isolated: self.is_network_isolated,
+        }
+    }
+}
+
+impl SandboxExecOptions {
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = vec![
+            "--cpu-seconds".to_string(),
+            self.cpu_seconds.to_string(),
+            "--memory-bytes".to_string(),
+            self.memory_bytes.to_string(),
+        ];
+
+        for write_path in &self.write_paths {
+            args.push("--write-path".to_string());
+            args.push(write_path.display().to_string());
+        }
+        for bind_port in &self.bind_ports {
+            args.push("--bind-port".to_string());
+            args.push(bind_port.to_string());
+        }
+        for connect_port in &self.connect_ports {
+            args.push("--connect-port".to_string());
+            args.push(connect_port.to_string());
+        }
+        if self.is_network_isolated {
+            args.push("--isolate-network".to_string());
+        }
+
+        args.push("--".to_string());
+        args
+    }
+
+    // returns the options and the command line that follows `--`
+    pub fn from_args(args: &[String]) -> Result<(Self, Vec<String>), String> {
+        let mut options = Self {
+            cpu_seconds: SandboxLimits::default().cpu_seconds,
+            memory_bytes: SandboxLimits::default().memory_bytes,
+            write_paths: Vec::new(),
+            bind_ports: Vec::new(),
+            connect_ports: Vec::new(),
+            is_network_isolated: false,
+        };
+
+        let mut args_iter = args.iter();
+        while let Some(flag) = args_iter.next() {
+            if flag == "--" {
+                let command_line: Vec<String> = args_iter.cloned().collect();
+                if command_line.is_empty() {
+                    return Err("Missing the command to run after --".to_string());
+                }
+                return Ok((options, command_line));
+            }
+
+            if flag == "--isolate-network" {
+                options.is_network_isolated = true;
+                continue;
+            }
+
+            let value = args_iter
+                .next()
+                .ok_or(format!("Missing value for {}", flag))?;
+            let invalid_value = |_| format!("Invalid value for {}: {}", flag, value);
+
+            match flag.as_str() {
+                "--cpu-seconds" => options.cpu_seconds = value.parse().map_err(invalid_value)?,
+                "--memory-bytes" => options.memory_bytes = value.parse().map_err(invalid_value)?,
+                "--write-path" => options.write_paths.push(PathBuf::from(value)),
+                "--bind-port" => options
+                    .bind_ports
+                    .push(value.parse().map_err(invalid_value)?),
+                "--connect-port" => options
+                    .connect_ports
+                    .push(value.parse().map_err(invalid_value)?),
+                _ => return Err(format!("Unknown sandbox-exec argument: {}", flag)),
+            }
+        }
+
+        Err("Missing -- before the command to run".to_string())
+    }
+}
+
+fn is_on_path(program: &str) -> bool {
+    std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).any(|dir| dir.join(program).is_file()))
+        .unwrap_or(false)
+}
+
+// the tool has to exist and unprivileged user namespaces have to be enabled
+fn is_tool_working(program: &str, probe_args: &[&str]) -> bool {
+    is_on_path(program)
+        && Command::new(program)
+            .args(probe_args)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false)
+}
+
+// what the machine can enforce, probed once in hoppity since the sandboxed children run on the same kernel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SandboxSupport {
+    pub tool: SandboxTool,
+    pub landlock_abi: Option<i64>,
+}
+
+impl SandboxSupport {
+    // unshare mounts / writable, only landlock keeps writes inside the write paths then
+    pub fn is_filesystem_confined(&self) -> bool {
+        self.tool == SandboxTool::Bubblewrap || self.landlock_abi.is_some()
+    }
+
+    // the server shares the host network, only landlock network rules stop it from ignoring the proxy
+    pub fn is_egress_enforced(&self) -> bool {
+        self.landlock_abi
+            .map(|abi| abi >= LANDLOCK_NET_ABI)
+            .unwrap_or(false)
+    }
+
+    fn degradations(&self) -> Vec<&'static str> {
+        let mut degradations: Vec<&'static str> = Vec::new();
+
+        if !self.is_filesystem_confined() {
+            degradations.push(
+                "Landlock is not available and the namespace sandbox keeps the filesystem writable, generated code can write anywhere the user can",
+            );
+        }
+        if !self.is_egress_enforced() {
+            degradations.push(
+                "Landlock network rules need Linux 6.7+, the server's outbound connections are only routed through the proxy, not enforced",
+            );
+        }
+
+        degradations
+    }
+}
+
+fn is_unsandboxed_allowed() -> bool {
+    std::env::var(ALLOW_UNSANDBOXED_ENV).map(|value| value == "1") == Ok(true)
+}
+
+// probed once, generated code never runs without a sandbox unless the user opts out
+pub fn sandbox_support() -> SandboxSupport {
+    static SANDBOX_SUPPORT: OnceLock<SandboxSupport> = OnceLock::new();
+
+    *SANDBOX_SUPPORT.get_or_init(|| {
+        let tool = if is_tool_working("bwrap", &["--ro-bind", "/", "/", "--unshare-user", "true"]) {
+            SandboxTool::Bubblewrap
+        } else if is_tool_working("unshare", &["--user", "--map-root-user", "true"]) {
+            SandboxTool::Unshare
+        } else {
+            if !is_unsandboxed_allowed() {
+                panic!(
+                    "No sandbox available for the generated code, install bubblewrap or enable unprivileged user namespaces. Set {}=1 to run it without one",
+                    ALLOW_UNSANDBOXED_ENV
+                );
+            }
+
+            PrintCommand::Issue.print_agent_action(
+                "Sandbox",
+                "No namespace sandbox available, generated code only runs with resource limits",
+            );
+            SandboxTool::Unsandboxed
+        };
+
+        let sandbox_support = SandboxSupport {
+            tool,
+            landlock_abi: landlock_abi(),
+        };
+
+        for degradation in sandbox_support.degradations() {
+            PrintCommand::Issue.print_agent_action("Sandbox", degradation);
+        }
+
+        sandbox_support
+    })
+}
+
+fn namespace_args(sandbox_tool: SandboxTool, policy: &SandboxPolicy) -> Vec<String> {
+    let mut args: Vec<String> = Vec::new();
+
+    match sandbox_tool {
+        SandboxTool::Bubblewrap => {
+            args.extend(
+                [
+                    "bwrap",
+                    "--ro-bind",
+                    "/",
+                    "/",
+                    "--dev",
+                    "/dev",
+                    "--proc",
+                    "/proc",
+                ]
+                .map(String::from),
+            );
+            args.extend(["--tmpfs", "/tmp"].map(String::from));
+
+            for writable_path in policy.writable_paths.iter().filter(|path| path.exists()) {
+                let writable_path = writable_path.display().to_string();
+                args.extend(["--bind".to_string(), writable_path.clone(), writable_path]);
+            }
+
+            args.extend(
+                [
+                    "--unshare-user",
+                    "--unshare-pid",
+                    "--unshare-ipc",
+                    "--unshare-uts",
+                    "--unshare-cgroup-try",
+                ]
+                .map(String::from),
+            );
+            if policy.is_network_isolated {
+                args.push("--unshare-net".to_string());
+            }
+
+            args.extend(["--die-with-parent", "--new-session", "--chdir"].map(String::from));
+            args.push(policy.project_root.display().to_string());
+        }
+        SandboxTool::Unshare => {
+            args.extend(
+                [
+                    "unshare",
+                    "--user",
+                    "--map-root-user",
+                    "--mount",
+                    "--pid",
+                    "--fork",
+                    "--kill-child",
+                    "--mount-proc",
+                    "--ipc",
+                    "--uts",
+                ]
+                .map(String::from),
+            );
+            if policy.is_network_isolated {
+                args.push("--net".to_string());
+            }
+        }
+        SandboxTool::Unsandboxed => return args,
+    }
+
+    args.push("--".to_string());
+    args
+}
+
+// namespaces (bubblewrap or unshare) -> hoppity sandbox-exec (limits, landlock) -> program
+pub fn sandboxed_command(program: &str, args: &[&str], policy: &SandboxPolicy) -> Command {
+    let sandbox_support = sandbox_support();
+
+    // the proxy variables alone do not stop code that opens its own connections
+    if !policy.is_network_isolated
+        && !sandbox_support.is_egress_enforced()
+        && !is_unsandboxed_allowed()
+    {
+        panic!(
+            "The kernel can not restrict the server's outbound connections (Landlock ABI {} needed), set {}=1 to run it anyway",
+            LANDLOCK_NET_ABI, ALLOW_UNSANDBOXED_ENV
+        );
+    }
+
+    let hoppity_exe = std::env::current_exe().expect("Failed to locate the hoppity executable");
+
+    let mut command_line = namespace_args(sandbox_support.tool, policy);
+    command_line.push(hoppity_exe.display().to_string());
+    command_line.push(SANDBOX_EXEC_COMMAND.to_string());
+    command_line.extend(policy.exec_options().to_args());
+    command_line.push(program.to_string());
+    command_line.extend(args.iter().map(|arg| arg.to_string()));
+
+    let mut command = Command::new(&command_line[0]);
+    command
+        .args(&command_line[1..])
+        .current_dir(&policy.project_root);
+
+    command
+}
+
+fn read_in_background<R: Read + Send + 'static>(reader: Option<R>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut output: Vec<u8> = Vec::new();
+        if let Some(mut reader) = reader {
+            let _ = reader.read_to_end(&mut output);
+        }
+        output
+    })
+}
+
+// like Command::output, but the process is killed once the wall time limit is reached
+pub fn output_with_timeout(mut command: Command, wall_time: Duration) -> std::io::Result<Output> {
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let stdout_reader = read_in_background(child.stdout.take());
+    let stderr_reader = read_in_background(child.stderr.take());
+
+    let started_at = Instant::now();
+    let (status, is_timed_out) = loop {
+        if let Some(status) = child.try_wait()? {
+            break (status, false);
+        }
+
+        if started_at.elapsed() > wall_time {
+            child.kill()?;
+            break (child.wait()?, true);
+        }
+
+        thread::sleep(Duration::from_millis(100));
+    };
+
+    let stdout = stdout_reader.join().unwrap_or_default();
+    let mut stderr = stderr_reader.join().unwrap_or_default();
+
+    if is_timed_out {
+        stderr.extend(
+            format!(
+                "\nerror: killed by the sandbox after the {}s wall time limit\n",
+                wall_time.as_secs()
+            )
+            .bytes(),
+        );
+    }
+
+    Ok(Output {
+        status,
+        stdout,
+        stderr,
+    })
+}
+
+fn landlock_abi() -> Option<i64> {
+    let abi = unsafe {
+        libc::syscall(
+            libc::SYS_landlock_create_ruleset,
+            std::ptr::null::<LandlockRulesetAttr>(),
+            0usize,
+            LANDLOCK_CREATE_RULESET_VERSION,
+        )
+    };
+
+    (abi > 0).then_some(abi)
+}
+
+fn landlock_add_rule<T>(
+    ruleset_fd: libc::c_int,
+    rule_type: libc::c_int,
+    rule: &T,
+) -> Result<(), String> {
+    let result = unsafe {
+        libc::syscall(
+            libc::SYS_landlock_add_rule,
+            ruleset_fd,
+            rule_type,
+            rule as *const T,
+            0u32,
+        )
+    };
+
+    if result < 0 {
+        return Err(format!(
+            "Failed to add a landlock rule: {}",
+            std::io::Error::last_os_error()
+        ));
+    }
+
+    Ok(())
+}
+
+// writes only under the write paths, tcp only on the allowed ports
+// returns false if the kernel has no landlock support
+fn restrict_with_landlock(options: &SandboxExecOptions) -> Result<bool, String> {
+    let Some(abi) = landlock_abi() else {
+        return Ok(false);
+    };
+
+    let truncate_access = if abi >= LANDLOCK_TRUNCATE_ABI {
+        LANDLOCK_ACCESS_FS_TRUNCATE
+    } else {
+        0
+    };
+    let handled_access_fs = LANDLOCK_ACCESS_FS_WRITE | truncate_access;
+
+    let is_net_handled = abi >= LANDLOCK_NET_ABI && !options.is_network_isolated;
+    let ruleset_attr = LandlockRulesetAttr {
+        handled_access_fs,
+        handled_access_net: if is_net_handled {
+            LANDLOCK_ACCESS_NET_BIND_TCP | LANDLOCK_ACCESS_NET_CONNECT_TCP
+        } else {
+            0
+        },
+    };
+
+    let ruleset_fd = unsafe {
+        libc::syscall(
+            libc::SYS_landlock_create_ruleset,
+            &ruleset_attr as *const LandlockRulesetAttr,
+            std::mem::size_of::<LandlockRulesetAttr>(),
+            0u32,
+        )
+    } as libc::c_int;
+
+    if ruleset_fd < 0 {
+        return Err(format!(
+            "Failed to create the landlock ruleset: {}",
+            std::io::Error::last_os_error()
+        ));
+    }
+
+    for write_path in options.write_paths.iter().filter(|path| path.exists()) {
+        let path_file: File = OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_PATH | libc::O_CLOEXEC)
+            .open(write_path)
+            .map_err(|e| format!("Failed to open {}: {}", write_path.display(), e))?;
+
+        // files only accept file rights, directories get every write right beneath them
+        let allowed_access = if write_path.is_dir() {
+            handled_access_fs
+        } else {
+            LANDLOCK_ACCESS_FS_WRITE_FILE | truncate_access
+        };
+
+        landlock_add_rule(
+            ruleset_fd,
+            LANDLOCK_RULE_PATH_BENEATH,
+            &LandlockPathBeneathAttr {
+                allowed_access,
+                parent_fd: path_file.as_raw_fd(),
+            },
+        )?;
+    }
+
+    if is_net_handled {
+        let net_rules = options
+            .bind_ports
+            .iter()
+            .map(|port| (LANDLOCK_ACCESS_NET_BIND_TCP, *port))
+            .chain(
+                options
+                    .connect_ports
+                    .iter()
+                    .map(|port| (LANDLOCK_ACCESS_NET_CONNECT_TCP, *port)),
+            );
+
+        for (allowed_access, port) in net_rules {
+            landlock_add_rule(
+                ruleset_fd,
+                LANDLOCK_RULE_NET_PORT,
+                &LandlockNetPortAttr {
+                    allowed_access,
+                    port: port as u64,
+                },
+            )?;
+        }
+    }
+
+    let result = unsafe { libc::syscall(libc::SYS_landlock_restrict_self, ruleset_fd, 0u32) };
+    unsafe { libc::close(ruleset_fd) };
+
+    if result < 0 {
+        return Err(format!(
+            "Failed to enforce the landlock ruleset: {}",
+            std::io::Error::last_os_error()
+        ));
+    }
+
+    Ok(true)
+}
+
+fn set_resource_limit(resource: libc::__rlimit_resource_t, limit: u64) -> Result<(), String> {
+    let rlimit = libc::rlimit {
+        rlim_cur: limit as libc::rlim_t,
+        rlim_max: limit as libc::rlim_t,
+    };
+
+    if unsafe { libc::setrlimit(resource, &rlimit) } != 0 {
+        return Err(format!(
+            "Failed to set a resource limit: {}",
+            std::io::Error::last_os_error()
+        ));
+    }
+
+    Ok(())
+}
+
+// entry point of `hoppity sandbox-exec`, only returns if the program could not be executed
+pub fn run_sandbox_exec(args: &[String]) -> ! {
+    let (options, command_line) =
+        SandboxExecOptions::from_args(args).expect("Invalid sandbox-exec arguments");
+
+    set_resource_limit(libc::RLIMIT_CPU, options.cpu_seconds).expect("Failed to limit cpu time");
+    set_resource_limit(libc::RLIMIT_AS, options.memory_bytes).expect("Failed to limit memory");
+
+    unsafe {
+        // landlock requires no_new_privs, and nothing outlives the sandbox that started it
+        libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0);
+        libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL);
+    }
+
+    // hoppity already reported a missing landlock before it started the sandbox
+    if let Err(e) = restrict_with_landlock(&options) {
+        panic!("{}", e);
+    }
+
+    let exec_error = Command::new(&command_line[0])
+        .args(&command_line[1..])
+        .exec();
+
+    panic!("Failed to execute {}: {}", command_line[0], exec_error);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sandbox_exec_options_round_trip() {
+        let policy = SandboxPolicy::server("/tmp/hoppity-bin", 8080, 41000);
+        let options = policy.exec_options();
+
+        let mut args = options.to_args();
+        args.extend(["cargo", "run", "--offline"].map(String::from));
+
+        let (parsed_options, command_line) = SandboxExecOptions::from_args(&args).unwrap();
+
+        assert_eq!(parsed_options, options);
+        assert_eq!(command_line, vec!["cargo", "run", "--offline"]);
+        assert!(SandboxExecOptions::from_args(&["--bind-port".to_string()]).is_err());
+        assert!(SandboxExecOptions::from_args(&["--".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_namespace_args() {
+        let offline_policy = SandboxPolicy::offline("/tmp");
+        let server_policy = SandboxPolicy::server("/tmp", 8080, 41000);
+
+        let bubblewrap_args = namespace_args(SandboxTool::Bubblewrap, &offline_policy);
+        assert_eq!(bubblewrap_args[0], "bwrap");
+        assert!(bubblewrap_args.contains(&"--unshare-net".to_string()));
+        assert_eq!(bubblewrap_args.last(), Some(&"--".to_string()));
+
+        assert!(offline_policy.writable_paths.contains(&cargo_home()));
+        assert_eq!(
+            server_policy.writable_paths,
+            vec![PathBuf::from("/tmp/data"), PathBuf::from("/tmp/target")]
+        );
+
+        let unshare_args = namespace_args(SandboxTool::Unshare, &server_policy);
+        assert_eq!(unshare_args[0], "unshare");
+        assert!(!unshare_args.contains(&"--net".to_string()));
+
+        assert!(namespace_args(SandboxTool::Unsandboxed, &offline_policy).is_empty());
+    }
+
+    #[test]
+    fn test_sandbox_degradations() {
+        let sandbox_support = |tool, landlock_abi| SandboxSupport { tool, landlock_abi };
+
+        assert!(sandbox_support(SandboxTool::Bubblewrap, Some(4))
+            .degradations()
+            .is_empty());
+        assert_eq!(
+            sandbox_support(SandboxTool::Bubblewrap, Some(3))
+                .degradations()
+                .len(),
+            1
+        );
+        assert!(sandbox_support(SandboxTool::Unshare, Some(1)).is_filesystem_confined());
+
+        let unconfined = sandbox_support(SandboxTool::Unshare, None);
+        assert!(!unconfined.is_filesystem_confined());
+        assert!(!unconfined.is_egress_enforced());
+        assert_eq!(unconfined.degradations().len(), 2);
+    }
+
+    #[test]
+    fn test_output_with_timeout() {
+        let mut sleep_command = Command::new("sleep");
+        sleep_command.arg("5");
+
+        let output = output_with_timeout(sleep_command, Duration::from_millis(200)).unwrap();
+
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("wall time limit"));
+    }
+}
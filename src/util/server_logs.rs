@@ -0,0 +1,158 @@
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+// a server that logs more than this keeps only the newest lines
+const MAX_LOG_LINES: usize = 5000;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LogLine {
+    // position in the whole log, stays valid when old lines are dropped
+    pub index: usize,
+    pub stream: LogStream,
+    pub text: String,
+}
+
+#[derive(Debug, Default)]
+struct LogBuffer {
+    lines: VecDeque<LogLine>,
+    next_index: usize,
+}
+
+// stdout and stderr of a running process, drained in the background so its pipes never fill up
+#[derive(Debug, Clone, Default)]
+pub struct ServerLogs {
+    buffer: Arc<Mutex<LogBuffer>>,
+    readers: Arc<Mutex<Vec<JoinHandle<()>>>>,
+}
+
+impl ServerLogs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn capture<R: Read + Send + 'static>(&self, reader: Option<R>, stream: LogStream) {
+        let Some(reader) = reader else {
+            return;
+        };
+
+        let buffer = Arc::clone(&self.buffer);
+        let log_reader = thread::spawn(move || {
+            for line in BufReader::new(reader).lines() {
+                let Ok(text) = line else {
+                    break;
+                };
+
+                let mut buffer = buffer.lock().unwrap();
+                let index = buffer.next_index;
+                buffer.next_index += 1;
+                buffer.lines.push_back(LogLine {
+                    index,
+                    stream,
+                    text,
+                });
+
+                if buffer.lines.len() > MAX_LOG_LINES {
+                    buffer.lines.pop_front();
+                }
+            }
+        });
+
+        self.readers.lock().unwrap().push(log_reader);
+    }
+
+    // index the next line will get, remember it before a request to find the lines it caused
+    pub fn next_index(&self) -> usize {
+        self.buffer.lock().unwrap().next_index
+    }
+
+    pub fn lines_since(&self, index: usize) -> Vec<LogLine> {
+        self.buffer
+            .lock()
+            .unwrap()
+            .lines
+            .iter()
+            .filter(|line| line.index >= index)
+            .cloned()
+            .collect()
+    }
+
+    pub fn tail(&self, line_count: usize) -> Vec<LogLine> {
+        let buffer = self.buffer.lock().unwrap();
+        buffer
+            .lines
+            .iter()
+            .skip(buffer.lines.len().saturating_sub(line_count))
+            .cloned()
+            .collect()
+    }
+
+    // once the process exited, give the readers a moment to pick up its last lines
+    pub fn wait_until_closed(&self, timeout: Duration) {
+        let started_at = Instant::now();
+
+        while started_at.elapsed() < timeout
+            && !self
+                .readers
+                .lock()
+                .unwrap()
+                .iter()
+                .all(|log_reader| log_reader.is_finished())
+        {
+            thread::sleep(Duration::from_millis(20));
+        }
+    }
+}
+
+pub fn format_log_lines(log_lines: &[LogLine]) -> String {
+    log_lines
+        .iter()
+        .map(|line| {
+            let stream = match line.stream {
+                LogStream::Stdout => "stdout",
+                LogStream::Stderr => "stderr",
+            };
+            format!("[{}] {}", stream, line.text)
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_server_logs() {
+        let server_logs = ServerLogs::new();
+        server_logs.capture(Some("listening\n".as_bytes()), LogStream::Stdout);
+        server_logs.wait_until_closed(Duration::from_secs(1));
+
+        let request_index = server_logs.next_index();
+        server_logs.capture(
+            Some("thread 'actix-rt' panicked\nbacktrace\n".as_bytes()),
+            LogStream::Stderr,
+        );
+        server_logs.wait_until_closed(Duration::from_secs(1));
+
+        let request_lines = server_logs.lines_since(request_index);
+        assert_eq!(request_lines.len(), 2);
+        assert_eq!(
+            format_log_lines(&request_lines),
+            "[stderr] thread 'actix-rt' panicked\n[stderr] backtrace"
+        );
+        assert_eq!(server_logs.tail(1)[0].text, "backtrace");
+        assert_eq!(server_logs.tail(10).len(), 3);
+    }
+}
@@ -0,0 +1,52 @@
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+// serve a single html document on a free local port, every GET request gets the same page
+// returns the port and the server task, abort the task to stop serving
+pub async fn serve_html(html: String) -> std::io::Result<(u16, JoinHandle<()>)> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let port = listener.local_addr()?.port();
+
+    let server_task = tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            // the request itself is irrelevant, but it has to be read before answering
+            let mut request_buffer = [0u8; 4096];
+            let _ = stream.read(&mut request_buffer).await;
+
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                html.len(),
+                html
+            );
+
+            let _ = stream.write_all(response.as_bytes()).await;
+            let _ = stream.shutdown().await;
+        }
+    });
+
+    Ok((port, server_task))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::provider::get_client;
+
+    #[tokio::test]
+    async fn test_serve_html() {
+        let html = "<html><body>hoppity</body></html>".to_string();
+        let (port, server_task) = serve_html(html.clone()).await.unwrap();
+
+        let response = get_client()
+            .get(format!("http://127.0.0.1:{}/", port))
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status().as_u16(), 200);
+        assert_eq!(response.text().await.unwrap(), html);
+
+        server_task.abort();
+    }
+}
@@ -0,0 +1,130 @@
+use std::fs::{File, OpenOptions};
+use std::sync::Mutex;
+
+use dotenv::dotenv;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{runtime, trace, Resource};
+use tracing::Subscriber;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::Layer;
+
+// closed spans as json lines, eg. HOPPITY_TRACE_FILE=trace.jsonl
+const TRACE_FILE_ENV: &str = "HOPPITY_TRACE_FILE";
+// an OTLP/HTTP collector, eg. HOPPITY_OTLP_ENDPOINT=http://localhost:4318/v1/traces
+const OTLP_ENDPOINT_ENV: &str = "HOPPITY_OTLP_ENDPOINT";
+const SERVICE_NAME: &str = "hoppity";
+
+// flushes the spans the OTLP exporter still holds when the run ends
+pub struct TracingGuard {
+    is_otlp_exporting: bool,
+}
+
+impl Drop for TracingGuard {
+    fn drop(&mut self) {
+        if self.is_otlp_exporting {
+            opentelemetry::global::shutdown_tracer_provider();
+        }
+    }
+}
+
+// one line per closed span, time.busy and time.idle add up to how long it was open
+pub fn json_file_layer<S>(trace_file: File) -> impl Layer<S>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    tracing_subscriber::fmt::layer()
+        .json()
+        .with_span_events(FmtSpan::CLOSE)
+        .with_span_list(true)
+        .with_writer(Mutex::new(trace_file))
+}
+
+fn otlp_layer<S>(endpoint: &str) -> impl Layer<S>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .http()
+                .with_endpoint(endpoint),
+        )
+        .with_trace_config(
+            trace::config().with_resource(Resource::new(vec![KeyValue::new(
+                "service.name",
+                SERVICE_NAME,
+            )])),
+        )
+        .install_batch(runtime::Tokio)
+        .unwrap_or_else(|error| panic!("Failed to start the OTLP exporter: {}", error));
+
+    tracing_opentelemetry::layer().with_tracer(tracer)
+}
+
+// spans are only recorded when a trace file or collector is configured
+pub fn init_tracing() -> TracingGuard {
+    dotenv().ok();
+
+    let trace_file = std::env::var(TRACE_FILE_ENV).ok().map(|trace_path| {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&trace_path)
+            .unwrap_or_else(|error| {
+                panic!("Failed to open the trace file {}: {}", trace_path, error)
+            })
+    });
+    let otlp_endpoint = std::env::var(OTLP_ENDPOINT_ENV).ok();
+
+    let is_otlp_exporting = otlp_endpoint.is_some();
+    if trace_file.is_none() && !is_otlp_exporting {
+        return TracingGuard { is_otlp_exporting };
+    }
+
+    tracing_subscriber::registry()
+        .with(trace_file.map(json_file_layer))
+        .with(otlp_endpoint.as_deref().map(otlp_layer))
+        .with(LevelFilter::INFO)
+        .init();
+
+    TracingGuard { is_otlp_exporting }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing::info_span;
+
+    #[test]
+    fn test_json_file_layer() {
+        let trace_path =
+            std::env::temp_dir().join(format!("hoppity_trace_{}.jsonl", std::process::id()));
+        let trace_file = File::create(&trace_path).unwrap();
+
+        let subscriber = tracing_subscriber::registry().with(json_file_layer(trace_file));
+        tracing::subscriber::with_default(subscriber, || {
+            let _agent_span = info_span!("agent", agent.position = "Backend Developer").entered();
+            let _build_span = info_span!("build_project").entered();
+        });
+
+        let trace = std::fs::read_to_string(&trace_path).unwrap();
+        let _ = std::fs::remove_file(&trace_path);
+        let spans: Vec<serde_json::Value> = trace
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        // inner spans close first
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[0]["span"]["name"], "build_project");
+        assert_eq!(spans[0]["spans"][0]["agent.position"], "Backend Developer");
+        assert!(spans[0]["fields"]["time.busy"].is_string());
+        assert_eq!(spans[1]["span"]["name"], "agent");
+    }
+}
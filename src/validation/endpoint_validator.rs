@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use reqwest::{Client, Method};
+use serde_json::Value;
+use tokio::time;
+
+use super::request_builder::{
+    capture_path_params, expected_statuses, fill_path_params, order_crud_routes, request_payload,
+};
+use super::response_shape::{check_response_shape, format_shape_mismatches, ShapeMismatch};
+use super::run_report::{EndpointResult, RunReport};
+use crate::model::common::api_schema::{FormattedRouteObject, HttpMethod};
+use crate::util::command_line::PrintCommand;
+use crate::util::event_bus::{publish, AgentEvent};
+use crate::util::project_runner::BackendServer;
+use crate::util::run_metrics::record_endpoint_result;
+
+// time the server gets to write the log lines of a failed request
+const LOG_SETTLE_TIME: Duration = Duration::from_millis(200);
+// attached instead when a failed request did not log anything itself
+const CONTEXT_LOG_LINES: usize = 20;
+
+// "http://localhost:41234" + "/item" -> "http://localhost:41234/item"
+pub fn endpoint_url(base_url: &str, route: &str) -> String {
+    format!(
+        "{}/{}",
+        base_url.trim_end_matches('/'),
+        route.trim_start_matches('/')
+    )
+}
+
+// send the request the schema describes, the body is kept if it is JSON
+async fn send_request(
+    client: &Client,
+    method: HttpMethod,
+    url: &str,
+    payload: Option<&Value>,
+    bearer_token: Option<&str>,
+) -> Result<(u16, Option<Value>), reqwest::Error> {
+    let method =
+        Method::from_bytes(method.as_str().as_bytes()).expect("Failed to convert the http method");
+
+    let mut request = client.request(method, url);
+    if let Some(payload) = payload {
+        request = request.json(payload);
+    }
+    if let Some(bearer_token) = bearer_token {
+        request = request.bearer_auth(bearer_token);
+    }
+
+    let response = request.send().await?;
+    let status_code = response.status().as_u16();
+
+    Ok((status_code, response.json::<Value>().await.ok()))
+}
+
+async fn validate_endpoint(
+    client: &Client,
+    backend_server: &BackendServer,
+    route_obj: &FormattedRouteObject,
+    chain_params: &[String],
+    param_values: &mut HashMap<String, String>,
+    bearer_token: Option<&str>,
+) -> EndpointResult {
+    let url = endpoint_url(
+        &backend_server.url,
+        &fill_path_params(&route_obj.route, param_values),
+    );
+    let payload = request_payload(route_obj);
+    let statuses = expected_statuses(route_obj.method);
+    let log_index = backend_server.logs.next_index();
+
+    let mut shape_mismatches: Vec<ShapeMismatch> = Vec::new();
+
+    let (status_code, error) = match send_request(
+        client,
+        route_obj.method,
+        &url,
+        payload.as_ref(),
+        bearer_token,
+    )
+    .await
+    {
+        Ok((status_code, response_body)) if statuses.contains(&status_code) => {
+            // what the create request sent and got back identifies the resource for the rest of the chain
+            capture_path_params(
+                route_obj,
+                chain_params,
+                &[response_body.as_ref(), payload.as_ref()],
+                param_values,
+            );
+
+            // the right status with the wrong structure still breaks the frontend
+            shape_mismatches = check_response_shape(&route_obj.response, response_body.as_ref());
+            let shape_error =
+                (!shape_mismatches.is_empty()).then(|| format_shape_mismatches(&shape_mismatches));
+
+            (Some(status_code), shape_error)
+        }
+        Ok((status_code, _)) => (
+            Some(status_code),
+            Some(format!(
+                "expected {}, got {}",
+                statuses
+                    .iter()
+                    .map(|status| status.to_string())
+                    .collect::<Vec<String>>()
+                    .join(" or "),
+                status_code
+            )),
+        ),
+        Err(e) => (None, Some(format!("request failed: {}", e))),
+    };
+
+    let log_lines = match error {
+        Some(_) => {
+            time::sleep(LOG_SETTLE_TIME).await;
+
+            let request_log_lines = backend_server.logs.lines_since(log_index);
+            if request_log_lines.is_empty() {
+                backend_server.logs.tail(CONTEXT_LOG_LINES)
+            } else {
+                request_log_lines
+            }
+        }
+        None => Vec::new(),
+    };
+
+    EndpointResult {
+        method: route_obj.method.to_string(),
+        route: route_obj.route.clone(),
+        url,
+        request_body: payload,
+        status_code,
+        is_passed: error.is_none(),
+        error,
+        shape_mismatches,
+        log_lines,
+    }
+}
+
+// call every endpoint of the running server, resource by resource as create -> read -> update -> delete
+// stops as soon as the server is gone, a bearer token is sent with every request once the server has logins
+pub async fn validate_endpoints(
+    client: &Client,
+    backend_server: &mut BackendServer,
+    api_schema: &[FormattedRouteObject],
+    agent_position: &str,
+    bearer_token: Option<&str>,
+) -> RunReport {
+    let mut run_report = RunReport::new(&backend_server.url);
+
+    let crud_groups = order_crud_routes(api_schema);
+
+    // path params of every route in a resource, eg. ["id"] for /item and /item/{id}
+    let group_params: Vec<Vec<String>> = crud_groups
+        .iter()
+        .map(|group| {
+            let mut params: Vec<String> = group
+                .iter()
+                .flat_map(|route_obj| route_obj.path_params.iter().cloned())
+                .collect();
+            params.sort();
+            params.dedup();
+            params
+        })
+        .collect();
+
+    let ordered_routes: Vec<(usize, &FormattedRouteObject)> = crud_groups
+        .into_iter()
+        .enumerate()
+        .flat_map(|(group_index, group)| {
+            group
+                .into_iter()
+                .map(move |route_obj| (group_index, route_obj))
+        })
+        .collect();
+
+    let mut param_values: HashMap<String, String> = HashMap::new();
+    let mut current_group_index: Option<usize> = None;
+
+    for (route_index, (group_index, route_obj)) in ordered_routes.iter().enumerate() {
+        if backend_server.has_exited() {
+            backend_server.logs.wait_until_closed(LOG_SETTLE_TIME);
+
+            // nothing left to call, the crash logs explain every remaining endpoint
+            for (_, skipped_route_obj) in &ordered_routes[route_index..] {
+                record_endpoint_result(false);
+                publish(AgentEvent::EndpointTested {
+                    agent: agent_position.to_string(),
+                    method: skipped_route_obj.method.to_string(),
+                    route: skipped_route_obj.route.clone(),
+                    status_code: None,
+                    is_passed: false,
+                });
+                run_report.endpoints.push(EndpointResult {
+                    method: skipped_route_obj.method.to_string(),
+                    route: skipped_route_obj.route.clone(),
+                    url: endpoint_url(&backend_server.url, &skipped_route_obj.route),
+                    request_body: request_payload(skipped_route_obj),
+                    status_code: None,
+                    is_passed: false,
+                    error: Some("the server stopped before this endpoint was called".to_string()),
+                    shape_mismatches: Vec::new(),
+                    log_lines: backend_server.logs.tail(CONTEXT_LOG_LINES),
+                });
+            }
+
+            PrintCommand::Issue.print_agent_action(
+                agent_position,
+                "The web server stopped, skipping the remaining endpoints",
+            );
+            break;
+        }
+
+        // every resource starts its chain without ids of another resource
+        if current_group_index != Some(*group_index) {
+            param_values.clear();
+            current_group_index = Some(*group_index);
+        }
+
+        PrintCommand::UnitTest.print_agent_action(
+            agent_position,
+            format!("Testing endpoint: {} {}", route_obj.method, route_obj.route).as_str(),
+        );
+
+        let endpoint_result = validate_endpoint(
+            client,
+            backend_server,
+            route_obj,
+            &group_params[*group_index],
+            &mut param_values,
+            bearer_token,
+        )
+        .await;
+        record_endpoint_result(endpoint_result.is_passed);
+        publish(AgentEvent::EndpointTested {
+            agent: agent_position.to_string(),
+            method: endpoint_result.method.clone(),
+            route: endpoint_result.route.clone(),
+            status_code: endpoint_result.status_code,
+            is_passed: endpoint_result.is_passed,
+        });
+
+        if let Some(error) = &endpoint_result.error {
+            PrintCommand::Issue.print_agent_action(
+                agent_position,
+                format!(
+                    "WARNING: {} {} {}",
+                    endpoint_result.method, endpoint_result.url, error
+                )
+                .as_str(),
+            );
+        }
+
+        run_report.endpoints.push(endpoint_result);
+    }
+
+    run_report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint_url() {
+        assert_eq!(
+            endpoint_url("http://localhost:41234", "/item"),
+            "http://localhost:41234/item"
+        );
+        assert_eq!(
+            endpoint_url("http://localhost:41234/", "item"),
+            "http://localhost:41234/item"
+        );
+    }
+}
@@ -0,0 +1,4 @@
+pub mod endpoint_validator;
+pub mod request_builder;
+pub mod response_shape;
+pub mod run_report;
@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+
+use serde_json::{json, Map, Value};
+
+use crate::model::common::api_schema::{FormattedRouteObject, HttpMethod, SchemaType};
+
+// value used for a path parameter nothing was created for yet
+pub const DEFAULT_PATH_PARAM: &str = "1";
+
+// a sample value of the schema type language, eg. "number" -> 1
+pub fn sample_value(schema: &SchemaType) -> Value {
+    match schema {
+        SchemaType::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, field_schema)| (key.clone(), sample_value(field_schema)))
+                .collect::<Map<String, Value>>(),
+        ),
+        SchemaType::Array(item_schema) => json!([sample_value(item_schema)]),
+        SchemaType::Optional(inner_schema) => sample_value(inner_schema),
+        SchemaType::Number => json!(1),
+        SchemaType::Float => json!(1.5),
+        SchemaType::Bool => json!(true),
+        SchemaType::None | SchemaType::NotProvided => Value::Null,
+        SchemaType::String | SchemaType::Other(_) => json!("hoppity"),
+    }
+}
+
+pub fn request_payload(route_obj: &FormattedRouteObject) -> Option<Value> {
+    (!route_obj.request_body.is_empty()).then(|| sample_value(&route_obj.request_body))
+}
+
+pub fn fill_path_params(route: &str, param_values: &HashMap<String, String>) -> String {
+    route
+        .split('/')
+        .map(|segment| {
+            match segment
+                .strip_prefix('{')
+                .and_then(|segment| segment.strip_suffix('}'))
+            {
+                Some(param) => param_values
+                    .get(param)
+                    .cloned()
+                    .unwrap_or(DEFAULT_PATH_PARAM.to_string()),
+                None => segment.to_string(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("/")
+}
+
+// status codes a correct server answers with, per method
+pub fn expected_statuses(method: HttpMethod) -> &'static [u16] {
+    match method {
+        HttpMethod::Post => &[200, 201],
+        HttpMethod::Put | HttpMethod::Patch | HttpMethod::Delete => &[200, 204],
+        _ => &[200],
+    }
+}
+
+// the part of a route before its first dynamic segment, "/item/{id}" and "/item" -> "/item"
+fn resource_key(route: &str) -> String {
+    let static_prefix = route.split('{').next().unwrap_or(route);
+    let resource = static_prefix.trim_end_matches('/');
+
+    if resource.is_empty() {
+        "/".to_string()
+    } else {
+        resource.to_string()
+    }
+}
+
+// create -> list -> read -> update -> delete, so reads and updates hit something that exists
+fn crud_rank(route_obj: &FormattedRouteObject) -> u8 {
+    match (route_obj.method, route_obj.is_route_dynamic) {
+        (HttpMethod::Post, false) => 0,
+        (HttpMethod::Get, false) => 1,
+        (HttpMethod::Get, true) => 2,
+        (HttpMethod::Put, _) | (HttpMethod::Patch, _) | (HttpMethod::Post, true) => 3,
+        (HttpMethod::Delete, _) => 5,
+        _ => 4,
+    }
+}
+
+// routes grouped by resource in schema order, each group ordered as a crud chain
+pub fn order_crud_routes(api_schema: &[FormattedRouteObject]) -> Vec<Vec<&FormattedRouteObject>> {
+    let mut resource_groups: Vec<(String, Vec<&FormattedRouteObject>)> = Vec::new();
+
+    for route_obj in api_schema {
+        let resource = resource_key(&route_obj.route);
+
+        match resource_groups
+            .iter_mut()
+            .find(|(group_resource, _)| *group_resource == resource)
+        {
+            Some((_, group)) => group.push(route_obj),
+            None => resource_groups.push((resource, vec![route_obj])),
+        }
+    }
+
+    resource_groups
+        .into_iter()
+        .map(|(_, mut group)| {
+            group.sort_by_key(|route_obj| crud_rank(route_obj));
+            group
+        })
+        .collect()
+}
+
+fn param_value(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    }
+}
+
+// values for the path params of later requests, taken from what a create request sent and got back
+// "{item_id}" falls back to the "id" field, an id captured once is kept for the rest of the chain
+pub fn capture_path_params(
+    route_obj: &FormattedRouteObject,
+    param_names: &[String],
+    sources: &[Option<&Value>],
+    param_values: &mut HashMap<String, String>,
+) {
+    // the sample payload of an update carries "id": 1, not the id of the created resource
+    if route_obj.method != HttpMethod::Post {
+        return;
+    }
+
+    for param_name in param_names {
+        if param_values.contains_key(param_name) {
+            continue;
+        }
+
+        let captured_value = sources.iter().flatten().find_map(|source| {
+            source
+                .get(param_name)
+                .or_else(|| {
+                    param_name
+                        .ends_with("id")
+                        .then(|| source.get("id"))
+                        .flatten()
+                })
+                .and_then(param_value)
+        });
+
+        if let Some(captured_value) = captured_value {
+            param_values.insert(param_name.clone(), captured_value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::common::api_schema::parse_path_params;
+
+    fn route_obj(route: &str, method: HttpMethod, request_body: Value) -> FormattedRouteObject {
+        FormattedRouteObject::new(
+            route,
+            method,
+            SchemaType::from(request_body),
+            SchemaType::None,
+        )
+    }
+
+    #[test]
+    fn test_request_payload() {
+        let create_item = route_obj(
+            "/item",
+            HttpMethod::Post,
+            json!({ "id": "number", "name": "string", "completed": "bool", "tags": ["string"] }),
+        );
+
+        assert_eq!(
+            request_payload(&create_item),
+            Some(json!({ "id": 1, "name": "hoppity", "completed": true, "tags": ["hoppity"] }))
+        );
+        assert_eq!(
+            request_payload(&route_obj("/item", HttpMethod::Get, json!("None"))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_fill_path_params() {
+        let mut param_values = HashMap::new();
+        capture_path_params(
+            &route_obj("/item", HttpMethod::Post, json!({ "tag": "string" })),
+            &parse_path_params("/item/{item_id}/tag/{tag}"),
+            &[Some(&json!({ "id": 42 })), Some(&json!({ "tag": "rust" }))],
+            &mut param_values,
+        );
+
+        assert_eq!(
+            fill_path_params("/item/{item_id}/tag/{tag}/{other}", &param_values),
+            "/item/42/tag/rust/1"
+        );
+    }
+
+    #[test]
+    fn test_update_keeps_captured_ids() {
+        let param_names = parse_path_params("/item/{id}");
+        let mut param_values = HashMap::new();
+
+        capture_path_params(
+            &route_obj("/item", HttpMethod::Post, json!({ "name": "string" })),
+            &param_names,
+            &[
+                Some(&json!({ "id": 42 })),
+                Some(&json!({ "name": "hoppity" })),
+            ],
+            &mut param_values,
+        );
+
+        // a PUT that answers 204 without a body only has its sample payload to offer
+        let update_item = route_obj(
+            "/item/{id}",
+            HttpMethod::Put,
+            json!({ "id": "number", "name": "string" }),
+        );
+        capture_path_params(
+            &update_item,
+            &param_names,
+            &[None, request_payload(&update_item).as_ref()],
+            &mut param_values,
+        );
+
+        assert_eq!(fill_path_params("/item/{id}", &param_values), "/item/42");
+    }
+
+    #[test]
+    fn test_order_crud_routes() {
+        let api_schema = vec![
+            route_obj("/item/{id}", HttpMethod::Delete, json!("None")),
+            route_obj("/crypto", HttpMethod::Get, json!("None")),
+            route_obj("/item/{id}", HttpMethod::Put, json!({ "name": "string" })),
+            route_obj("/item/{id}", HttpMethod::Get, json!("None")),
+            route_obj("/item", HttpMethod::Post, json!({ "name": "string" })),
+        ];
+
+        let ordered_routes: Vec<Vec<String>> = order_crud_routes(&api_schema)
+            .iter()
+            .map(|group| {
+                group
+                    .iter()
+                    .map(|route_obj| format!("{} {}", route_obj.method, route_obj.route))
+                    .collect()
+            })
+            .collect();
+
+        assert_eq!(
+            ordered_routes,
+            vec![
+                vec![
+                    "POST /item",
+                    "GET /item/{id}",
+                    "PUT /item/{id}",
+                    "DELETE /item/{id}"
+                ],
+                vec!["GET /crypto"],
+            ]
+        );
+        assert_eq!(expected_statuses(HttpMethod::Post), &[200, 201]);
+    }
+}
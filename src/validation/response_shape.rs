@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::model::common::api_schema::SchemaType;
+
+// one place where the response body differs from the schema, eg. "response.items[0].id"
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ShapeMismatch {
+    pub path: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+// the json type name used in mismatch messages
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "bool",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+// whether a value fits a leaf of the schema, names the type language does not know accept anything
+fn is_type_matched(schema: &SchemaType, value: &Value) -> bool {
+    match schema {
+        SchemaType::Number | SchemaType::Float => value.is_number(),
+        SchemaType::Bool => value.is_boolean(),
+        SchemaType::String => value.is_string(),
+        _ => true,
+    }
+}
+
+fn collect_mismatches(
+    schema: &SchemaType,
+    value: &Value,
+    path: &str,
+    mismatches: &mut Vec<ShapeMismatch>,
+) {
+    match schema {
+        SchemaType::Object(fields) => {
+            let Some(object) = value.as_object() else {
+                mismatches.push(ShapeMismatch {
+                    path: path.to_string(),
+                    expected: "object".to_string(),
+                    actual: json_type(value).to_string(),
+                });
+                return;
+            };
+
+            // every key of the schema is required, unless it is optional
+            for (key, field_schema) in fields {
+                let field_path = format!("{}.{}", path, key);
+
+                match object.get(key) {
+                    Some(field_value) => {
+                        collect_mismatches(field_schema, field_value, &field_path, mismatches)
+                    }
+                    None if matches!(field_schema, SchemaType::Optional(_)) => {}
+                    None => mismatches.push(ShapeMismatch {
+                        path: field_path,
+                        expected: "required key".to_string(),
+                        actual: "missing".to_string(),
+                    }),
+                }
+            }
+        }
+        SchemaType::Array(item_schema) => {
+            let Some(array) = value.as_array() else {
+                mismatches.push(ShapeMismatch {
+                    path: path.to_string(),
+                    expected: "array".to_string(),
+                    actual: json_type(value).to_string(),
+                });
+                return;
+            };
+
+            for (index, item) in array.iter().enumerate() {
+                collect_mismatches(
+                    item_schema,
+                    item,
+                    &format!("{}[{}]", path, index),
+                    mismatches,
+                );
+            }
+        }
+        SchemaType::Optional(_) if value.is_null() => {}
+        SchemaType::Optional(inner_schema) => {
+            collect_mismatches(inner_schema, value, path, mismatches)
+        }
+        leaf_schema if !is_type_matched(leaf_schema, value) => {
+            mismatches.push(ShapeMismatch {
+                path: path.to_string(),
+                expected: Value::from(leaf_schema.clone())
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                actual: json_type(value).to_string(),
+            });
+        }
+        _ => {}
+    }
+}
+
+// compare a response body with the "response" shape of the extracted schema
+pub fn check_response_shape(
+    schema: &SchemaType,
+    response_body: Option<&Value>,
+) -> Vec<ShapeMismatch> {
+    if schema.is_empty() {
+        return Vec::new();
+    }
+
+    let Some(response_body) = response_body else {
+        return vec![ShapeMismatch {
+            path: "response".to_string(),
+            expected: "json body".to_string(),
+            actual: "no json body".to_string(),
+        }];
+    };
+
+    let mut mismatches = Vec::new();
+    collect_mismatches(schema, response_body, "response", &mut mismatches);
+    mismatches
+}
+
+pub fn format_shape_mismatches(mismatches: &[ShapeMismatch]) -> String {
+    mismatches
+        .iter()
+        .map(|mismatch| {
+            format!(
+                "RESPONSE_SHAPE_MISMATCH at {}: expected {}, got {}",
+                mismatch.path, mismatch.expected, mismatch.actual
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_check_response_shape() {
+        let item_schema =
+            SchemaType::from(json!({ "id": "number", "name": "string", "completed": "bool" }));
+
+        assert!(check_response_shape(
+            &item_schema,
+            Some(&json!({ "id": 1, "name": "milk", "completed": false, "extra": null }))
+        )
+        .is_empty());
+        assert!(check_response_shape(&SchemaType::NotProvided, None).is_empty());
+
+        assert_eq!(
+            format_shape_mismatches(&check_response_shape(
+                &item_schema,
+                Some(&json!({ "id": "1", "completed": false }))
+            )),
+            "RESPONSE_SHAPE_MISMATCH at response.id: expected number, got string\nRESPONSE_SHAPE_MISMATCH at response.name: expected required key, got missing"
+        );
+
+        let list_mismatches = check_response_shape(
+            &SchemaType::Array(Box::new(item_schema)),
+            Some(&json!({ "id": 1, "name": "milk", "completed": false })),
+        );
+        assert_eq!(list_mismatches[0].expected, "array");
+        assert_eq!(list_mismatches[0].actual, "object");
+
+        let optional_schema = SchemaType::from(json!({ "id": "number", "note?": "string" }));
+        assert!(check_response_shape(&optional_schema, Some(&json!({ "id": 1 }))).is_empty());
+        assert!(
+            check_response_shape(&optional_schema, Some(&json!({ "id": 1, "note": null })))
+                .is_empty()
+        );
+        assert_eq!(
+            check_response_shape(&optional_schema, Some(&json!({ "id": 1, "note": 2 })))[0].path,
+            "response.note"
+        );
+
+        let element_mismatches = check_response_shape(
+            &SchemaType::from(json!([{ "id": "number" }])),
+            Some(&json!([{ "id": 1 }, { "id": true }])),
+        );
+        assert_eq!(element_mismatches[0].path, "response[1].id");
+    }
+}
@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+
+use super::response_shape::ShapeMismatch;
+use crate::util::server_logs::{format_log_lines, LogLine};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct EndpointResult {
+    pub method: String,
+    pub route: String,
+    pub url: String,
+    pub request_body: Option<serde_json::Value>,
+    pub status_code: Option<u16>,
+    pub is_passed: bool,
+    pub error: Option<String>,
+    // where the response body differs from the "response" shape of the schema
+    pub shape_mismatches: Vec<ShapeMismatch>,
+    // what the server logged while handling the request, only kept for failed endpoints
+    pub log_lines: Vec<LogLine>,
+}
+
+// outcome of validating every endpoint of one running server
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RunReport {
+    pub server_url: String,
+    pub endpoints: Vec<EndpointResult>,
+}
+
+impl RunReport {
+    pub fn new(server_url: &str) -> Self {
+        Self {
+            server_url: server_url.to_string(),
+            endpoints: Vec::new(),
+        }
+    }
+
+    pub fn passed_count(&self) -> usize {
+        self.endpoints
+            .iter()
+            .filter(|endpoint| endpoint.is_passed)
+            .count()
+    }
+
+    pub fn failed_endpoints(&self) -> Vec<&EndpointResult> {
+        self.endpoints
+            .iter()
+            .filter(|endpoint| !endpoint.is_passed)
+            .collect()
+    }
+
+    // every failed endpoint with its error and the server logs it caused
+    pub fn failure_summary(&self) -> String {
+        self.failed_endpoints()
+            .iter()
+            .map(|endpoint| {
+                let mut failure = format!(
+                    "{} {}: {}",
+                    endpoint.method,
+                    endpoint.route,
+                    endpoint.error.clone().unwrap_or_default()
+                );
+
+                if !endpoint.log_lines.is_empty() {
+                    failure.push_str(&format!(
+                        "\nserver logs:\n{}",
+                        format_log_lines(&endpoint.log_lines)
+                    ));
+                }
+
+                failure
+            })
+            .collect::<Vec<String>>()
+            .join("\n\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::server_logs::LogStream;
+
+    fn endpoint_result(route: &str, status_code: u16, log_lines: Vec<LogLine>) -> EndpointResult {
+        EndpointResult {
+            method: "GET".to_string(),
+            route: route.to_string(),
+            url: format!("http://localhost:8080{}", route),
+            request_body: None,
+            status_code: Some(status_code),
+            is_passed: status_code == 200,
+            error: (status_code != 200).then(|| format!("expected 200, got {}", status_code)),
+            shape_mismatches: Vec::new(),
+            log_lines,
+        }
+    }
+
+    #[test]
+    fn test_failure_summary() {
+        let panic_line = LogLine {
+            index: 7,
+            stream: LogStream::Stderr,
+            text: "thread 'actix-rt|system:0|arbiter:0' panicked at src/main.rs:42:10".to_string(),
+        };
+
+        let run_report = RunReport {
+            server_url: "http://localhost:8080".to_string(),
+            endpoints: vec![
+                endpoint_result("/items", 200, vec![]),
+                endpoint_result("/crypto", 500, vec![panic_line]),
+            ],
+        };
+
+        assert_eq!(run_report.passed_count(), 1);
+        assert_eq!(
+            run_report.failure_summary(),
+            "GET /crypto: expected 200, got 500\nserver logs:\n[stderr] thread 'actix-rt|system:0|arbiter:0' panicked at src/main.rs:42:10"
+        );
+    }
+}